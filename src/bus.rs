@@ -1,16 +1,28 @@
 use crate::cartridge::Rom;
+use crate::coverage::Coverage;
 use crate::cpu::Mem;
+use crate::heatmap::{Access, Heatmap};
+use std::cell::RefCell;
 
 const RAM: u16 = 0x0000;
 const RAM_MIRRORS_END: u16 = 0x1fff;
-const PPU_REGISTERS: u16 = 0x2000;
-const PPU_REGISTERS_MIRRORS_END: u16 = 0x3fff;
-const PRG_ROM: u16 = 0x8000;
+pub(crate) const PPU_REGISTERS: u16 = 0x2000;
+pub(crate) const PPU_REGISTERS_MIRRORS_END: u16 = 0x3fff;
+pub(crate) const PRG_ROM: u16 = 0x8000;
 const PRG_ROM_END: u16 = 0xffff;
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MemoryDomain {
+    CpuRam,
+    PrgRom,
+    ChrRom,
+}
+
 pub struct Bus {
     cpu_vram: [u8; 2048],
     rom: Rom,
+    heatmap: RefCell<Option<Heatmap>>,
+    coverage: RefCell<Option<Coverage>>,
 }
 
 impl Bus {
@@ -18,20 +30,118 @@ impl Bus {
         Bus {
             cpu_vram: [0; 2048],
             rom,
+            heatmap: RefCell::new(None),
+            coverage: RefCell::new(None),
+        }
+    }
+
+    pub fn rom(&self) -> &Rom {
+        &self.rom
+    }
+
+    pub fn patch_prg_rom(&mut self, addr: u16, data: u8) -> Result<(), String> {
+        if addr < PRG_ROM {
+            return Err(format!("Address {:#06x} is outside of PRG ROM", addr));
+        }
+        let offset = self.prg_rom_offset(addr);
+        self.rom.patch_prg_rom(offset, data)
+    }
+
+    pub fn undo_rom_patch(&mut self) -> bool {
+        self.rom.undo_patch()
+    }
+
+    pub fn domain_size(&self, domain: MemoryDomain) -> usize {
+        match domain {
+            MemoryDomain::CpuRam => self.cpu_vram.len(),
+            MemoryDomain::PrgRom => self.rom.prg_rom.len(),
+            MemoryDomain::ChrRom => self.rom.chr_rom.len(),
+        }
+    }
+
+    pub fn peek(&self, domain: MemoryDomain, offset: usize) -> Option<u8> {
+        match domain {
+            MemoryDomain::CpuRam => self.cpu_vram.get(offset).copied(),
+            MemoryDomain::PrgRom => self.rom.prg_rom.get(offset).copied(),
+            MemoryDomain::ChrRom => self.rom.chr_rom.get(offset).copied(),
+        }
+    }
+
+    pub fn peek_cpu(&self, addr: u16) -> Option<u8> {
+        match addr {
+            RAM..=RAM_MIRRORS_END => {
+                let mirror_down_addr = addr & 0b00000111_11111111;
+                Some(self.cpu_vram[mirror_down_addr as usize])
+            }
+            PRG_ROM..=PRG_ROM_END => Some(self.read_prg_rom(addr)),
+            _ => None,
+        }
+    }
+
+    pub fn poke(&mut self, domain: MemoryDomain, offset: usize, data: u8) -> Result<(), String> {
+        if offset >= self.domain_size(domain) {
+            return Err(format!("{:?} offset {:#x} is out of range", domain, offset));
+        }
+
+        match domain {
+            MemoryDomain::CpuRam => self.cpu_vram[offset] = data,
+            MemoryDomain::PrgRom => self.rom.patch_prg_rom(offset, data)?,
+            MemoryDomain::ChrRom => self.rom.patch_chr_rom(offset, data)?,
+        }
+        Ok(())
+    }
+
+    pub fn start_heatmap(&mut self, granularity: usize) {
+        *self.heatmap.get_mut() = Some(Heatmap::new(granularity));
+    }
+
+    pub fn stop_heatmap(&mut self) -> Option<Heatmap> {
+        self.heatmap.take()
+    }
+
+    pub fn start_coverage(&mut self) {
+        *self.coverage.get_mut() = Some(Coverage::new(self.rom.prg_rom.len()));
+    }
+
+    pub fn stop_coverage(&mut self) -> Option<Coverage> {
+        self.coverage.take()
+    }
+
+    pub fn record_execute(&self, addr: u16, len: u8) {
+        self.record(Access::Execute, addr);
+
+        if let Some(coverage) = self.coverage.borrow_mut().as_mut() {
+            for i in 0..len as u16 {
+                let addr = addr.wrapping_add(i);
+                if addr >= PRG_ROM {
+                    coverage.record(self.prg_rom_offset(addr));
+                }
+            }
         }
     }
 
-    fn read_prg_rom(&self, mut addr: u16) -> u8 {
+    fn record(&self, access: Access, addr: u16) {
+        if let Some(heatmap) = self.heatmap.borrow_mut().as_mut() {
+            heatmap.record(access, addr);
+        }
+    }
+
+    fn prg_rom_offset(&self, mut addr: u16) -> usize {
         addr -= 0x8000;
         if self.rom.prg_rom.len() == 0x4000 && addr >= 0x4000 {
             addr %= 0x4000;
         }
-        self.rom.prg_rom[addr as usize]
+        addr as usize
+    }
+
+    fn read_prg_rom(&self, addr: u16) -> u8 {
+        self.rom.prg_rom[self.prg_rom_offset(addr)]
     }
 }
 
 impl Mem for Bus {
     fn mem_read(&self, addr: u16) -> u8 {
+        self.record(Access::Read, addr);
         match addr {
             RAM..=RAM_MIRRORS_END => {
                 let mirror_down_addr = addr & 0b00000111_11111111;
@@ -50,6 +160,7 @@ impl Mem for Bus {
     }
 
     fn mem_write(&mut self, addr: u16, data: u8) {
+        self.record(Access::Write, addr);
         match addr {
             RAM..=RAM_MIRRORS_END => {
                 let mirror_down_addr = addr & 0b00000111_11111111;
@@ -68,3 +179,92 @@ impl Mem for Bus {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::test::test_rom;
+
+    #[test]
+    fn test_poke_cpu_ram_is_visible_through_mirrors() {
+        let mut bus = Bus::new(test_rom());
+        bus.poke(MemoryDomain::CpuRam, 0x0123, 0x45).unwrap();
+        assert_eq!(bus.mem_read(0x0923), 0x45);
+        assert_eq!(bus.peek(MemoryDomain::CpuRam, 0x0123), Some(0x45));
+    }
+
+    #[test]
+    fn test_poke_prg_rom_can_be_undone() {
+        let mut bus = Bus::new(test_rom());
+        bus.poke(MemoryDomain::PrgRom, 0x0010, 0xea).unwrap();
+        assert_eq!(bus.mem_read(0x8010), 0xea);
+        assert_eq!(bus.mem_read(0xc010), 0xea);
+        assert!(bus.undo_rom_patch());
+        assert_eq!(bus.peek(MemoryDomain::PrgRom, 0x0010), Some(0x00));
+    }
+
+    #[test]
+    fn test_poke_chr_rom_can_be_undone() {
+        let mut bus = Bus::new(test_rom());
+        bus.poke(MemoryDomain::ChrRom, 0x0010, 0xff).unwrap();
+        assert_eq!(bus.peek(MemoryDomain::ChrRom, 0x0010), Some(0xff));
+        assert!(bus.undo_rom_patch());
+        assert_eq!(bus.peek(MemoryDomain::ChrRom, 0x0010), Some(0x00));
+        assert!(!bus.undo_rom_patch());
+    }
+
+    #[test]
+    fn test_peek_cpu_has_no_side_effects() {
+        let mut bus = Bus::new(test_rom());
+        bus.mem_write(0x0010, 0x42);
+        bus.start_heatmap(1);
+
+        assert_eq!(bus.peek_cpu(0x0810), Some(0x42));
+        assert_eq!(bus.peek_cpu(0xfffc), Some(0x00));
+        assert_eq!(bus.peek_cpu(0x2002), None);
+        assert_eq!(bus.peek_cpu(0x5000), None);
+        assert_eq!(bus.stop_heatmap().unwrap().reads().iter().sum::<u32>(), 0);
+    }
+
+    #[test]
+    fn test_domain_bounds() {
+        let mut bus = Bus::new(test_rom());
+        assert_eq!(bus.domain_size(MemoryDomain::CpuRam), 0x0800);
+        assert_eq!(bus.domain_size(MemoryDomain::ChrRom), 0x2000);
+        assert_eq!(bus.peek(MemoryDomain::PrgRom, 0x4000), None);
+        assert!(bus.poke(MemoryDomain::ChrRom, 0x2000, 0xff).is_err());
+    }
+
+    #[test]
+    fn test_heatmap_splits_reads_writes_and_executes() {
+        let mut bus = Bus::new(test_rom());
+        bus.start_heatmap(0x100);
+        bus.mem_write(0x0010, 0x01);
+        bus.mem_read(0x0010);
+        bus.mem_read(0x00ff);
+        bus.record_execute(0x8000, 3);
+
+        let heatmap = bus.stop_heatmap().unwrap();
+        assert_eq!(heatmap.reads()[0x00], 2);
+        assert_eq!(heatmap.writes()[0x00], 1);
+        assert_eq!(heatmap.executes()[0x00], 0);
+        assert_eq!(heatmap.executes()[0x80], 1);
+        assert_eq!(heatmap.reads()[0x80], 0);
+        assert!(bus.stop_heatmap().is_none());
+    }
+
+    #[test]
+    fn test_coverage_records_operands_through_mirror() {
+        let mut bus = Bus::new(test_rom());
+        bus.start_coverage();
+        bus.record_execute(0xc010, 3);
+        bus.record_execute(0x0600, 2);
+        bus.record_execute(0xffff, 2);
+
+        let coverage = bus.stop_coverage().unwrap();
+        let executed: Vec<usize> = (0..coverage.executed().len())
+            .filter(|&i| coverage.executed()[i])
+            .collect();
+        assert_eq!(executed, vec![0x0010, 0x0011, 0x0012, 0x3fff]);
+    }
+}
@@ -1,5 +1,8 @@
+use crate::ips;
+use std::path::Path;
+
 const NES_TAG: [u8; 4] = [0x4e, 0x45, 0x53, 0x1a];
-const PRG_ROM_PAGE_SIZE: usize = 16 * 1024;
+pub const PRG_ROM_PAGE_SIZE: usize = 16 * 1024;
 const CHR_ROM_PAGE_SIZE: usize = 8 * 1024;
 
 #[derive(Debug, PartialEq)]
@@ -9,16 +12,24 @@ pub enum Mirroring {
     FourScreen,
 }
 
+enum Bank {
+    PrgRom,
+    ChrRom,
+}
+
 pub struct Rom {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
     pub mapper: u8,
     pub screen_mirroring: Mirroring,
+    raw: Vec<u8>,
+    prg_rom_start: usize,
+    patches: Vec<(Bank, usize, u8)>,
 }
 
 impl Rom {
-    pub fn new(raw: &Vec<u8>) -> Result<Rom, String> {
-        if &raw[0..4] != NES_TAG {
+    pub fn new(raw: &[u8]) -> Result<Rom, String> {
+        if raw[0..4] != NES_TAG {
             return Err("File is not in iNES file format".to_string());
         }
 
@@ -50,6 +61,175 @@ impl Rom {
             chr_rom: raw[chr_rom_start..(chr_rom_start + chr_rom_size)].to_vec(),
             mapper,
             screen_mirroring,
+            raw: raw.to_vec(),
+            prg_rom_start,
+            patches: vec![],
         })
     }
+
+    pub fn patch_prg_rom(&mut self, offset: usize, data: u8) -> Result<(), String> {
+        if offset >= self.prg_rom.len() {
+            return Err(format!("PRG ROM offset {:#x} is out of range", offset));
+        }
+
+        self.patches
+            .push((Bank::PrgRom, offset, self.prg_rom[offset]));
+        self.prg_rom[offset] = data;
+        Ok(())
+    }
+
+    pub fn patch_chr_rom(&mut self, offset: usize, data: u8) -> Result<(), String> {
+        if offset >= self.chr_rom.len() {
+            return Err(format!("CHR ROM offset {:#x} is out of range", offset));
+        }
+
+        self.patches
+            .push((Bank::ChrRom, offset, self.chr_rom[offset]));
+        self.chr_rom[offset] = data;
+        Ok(())
+    }
+
+    pub fn undo_patch(&mut self) -> bool {
+        match self.patches.pop() {
+            Some((Bank::PrgRom, offset, data)) => {
+                self.prg_rom[offset] = data;
+                true
+            }
+            Some((Bank::ChrRom, offset, data)) => {
+                self.chr_rom[offset] = data;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let chr_rom_start = self.prg_rom_start + self.prg_rom.len();
+        let mut bytes = self.raw.clone();
+        bytes[self.prg_rom_start..chr_rom_start].copy_from_slice(&self.prg_rom);
+        bytes[chr_rom_start..(chr_rom_start + self.chr_rom.len())].copy_from_slice(&self.chr_rom);
+        bytes
+    }
+
+    pub fn ips_patch(&self) -> Result<Vec<u8>, String> {
+        ips::create_patch(&self.raw, &self.to_bytes())
+    }
+
+    pub fn export_rom<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes()).map_err(|e| e.to_string())
+    }
+
+    pub fn export_ips<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        std::fs::write(path, self.ips_patch()?).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    pub fn test_rom() -> Rom {
+        let mut raw = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x01, 0x00, 0x00];
+        raw.resize(16, 0);
+
+        let mut prg_rom = vec![0; PRG_ROM_PAGE_SIZE];
+        prg_rom[0x3ffa] = 0x80;
+        prg_rom[0x3ffb] = 0x07;
+        prg_rom[0x3ffc] = 0x00;
+        prg_rom[0x3ffd] = 0x06;
+        prg_rom[0x3ffe] = 0x00;
+        prg_rom[0x3fff] = 0x07;
+        raw.extend(prg_rom);
+        raw.extend(vec![0; CHR_ROM_PAGE_SIZE]);
+
+        Rom::new(&raw).unwrap()
+    }
+
+    #[test]
+    fn test_patch_prg_rom_and_undo() {
+        let mut rom = test_rom();
+        rom.patch_prg_rom(0x0010, 0xea).unwrap();
+        rom.patch_prg_rom(0x0010, 0x4c).unwrap();
+        assert_eq!(rom.prg_rom[0x0010], 0x4c);
+
+        assert!(rom.undo_patch());
+        assert_eq!(rom.prg_rom[0x0010], 0xea);
+        assert!(rom.undo_patch());
+        assert_eq!(rom.prg_rom[0x0010], 0x00);
+        assert!(!rom.undo_patch());
+    }
+
+    #[test]
+    fn test_patches_undo_across_banks() {
+        let mut rom = test_rom();
+        rom.patch_prg_rom(0x0010, 0xea).unwrap();
+        rom.patch_chr_rom(0x0010, 0xff).unwrap();
+
+        assert!(rom.undo_patch());
+        assert_eq!(rom.chr_rom[0x0010], 0x00);
+        assert_eq!(rom.prg_rom[0x0010], 0xea);
+        assert!(rom.undo_patch());
+        assert_eq!(rom.prg_rom[0x0010], 0x00);
+    }
+
+    #[test]
+    fn test_patch_prg_rom_out_of_range() {
+        let mut rom = test_rom();
+        assert!(rom.patch_prg_rom(PRG_ROM_PAGE_SIZE, 0xea).is_err());
+        assert!(!rom.undo_patch());
+    }
+
+    #[test]
+    fn test_ips_patch_follows_undo() {
+        let mut rom = test_rom();
+        rom.patch_prg_rom(0x0010, 0xea).unwrap();
+        assert_eq!(
+            rom.ips_patch(),
+            Ok(b"PATCH\x00\x00\x20\x00\x01\xeaEOF".to_vec())
+        );
+
+        rom.undo_patch();
+        assert_eq!(rom.ips_patch(), Ok(b"PATCHEOF".to_vec()));
+    }
+
+    #[test]
+    fn test_to_bytes_round_trip() {
+        let mut rom = test_rom();
+        rom.patch_prg_rom(0x0010, 0xea).unwrap();
+        rom.chr_rom[0x0100] = 0xff;
+
+        let bytes = rom.to_bytes();
+        let reloaded = Rom::new(&bytes).unwrap();
+        assert_eq!(reloaded.prg_rom, rom.prg_rom);
+        assert_eq!(reloaded.chr_rom, rom.chr_rom);
+        assert_eq!(
+            ips::test::apply_patch(&rom.raw, &rom.ips_patch().unwrap()),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_export_rom_and_ips() {
+        let mut rom = test_rom();
+        rom.patch_prg_rom(0x3ffc, 0x00).unwrap();
+        rom.patch_prg_rom(0x3ffd, 0x80).unwrap();
+        rom.chr_rom[0] = 0x01;
+
+        let dir = std::env::temp_dir().join(format!("nes_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        rom.export_rom(dir.join("patched.nes")).unwrap();
+        rom.export_ips(dir.join("patched.ips")).unwrap();
+        let exported_rom = std::fs::read(dir.join("patched.nes")).unwrap();
+        let exported_ips = std::fs::read(dir.join("patched.ips")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(exported_rom, rom.to_bytes());
+        assert_eq!(
+            ips::test::apply_patch(&rom.raw, &exported_ips),
+            exported_rom
+        );
+        assert!(rom
+            .export_rom(dir.join("missing").join("patched.nes"))
+            .is_err());
+    }
 }
@@ -0,0 +1,109 @@
+use crate::cartridge::PRG_ROM_PAGE_SIZE;
+use std::fmt::Write;
+
+pub struct Coverage {
+    executed: Vec<bool>,
+}
+
+impl Coverage {
+    pub fn new(prg_rom_size: usize) -> Self {
+        Coverage {
+            executed: vec![false; prg_rom_size],
+        }
+    }
+
+    pub fn record(&mut self, offset: usize) {
+        self.executed[offset] = true;
+    }
+
+    pub fn executed(&self) -> &[bool] {
+        &self.executed
+    }
+
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let total = self.executed.iter().filter(|&&hit| hit).count();
+        writeln!(
+            report,
+            "PRG ROM: {}/{} bytes executed ({:.1}%)",
+            total,
+            self.executed.len(),
+            percent(total, self.executed.len())
+        )
+        .unwrap();
+
+        for (bank, executed) in self.executed.chunks(PRG_ROM_PAGE_SIZE).enumerate() {
+            let hits = executed.iter().filter(|&&hit| hit).count();
+            writeln!(
+                report,
+                "Bank {}: {}/{} bytes executed ({:.1}%)",
+                bank,
+                hits,
+                executed.len(),
+                percent(hits, executed.len())
+            )
+            .unwrap();
+
+            let mut start = None;
+            for (offset, &hit) in executed.iter().chain([false].iter()).enumerate() {
+                match (hit, start) {
+                    (true, None) => start = Some(offset),
+                    (false, Some(from)) => {
+                        writeln!(report, "  {:04x}-{:04x}", from, offset - 1).unwrap();
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        report
+    }
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report_lists_ranges_per_bank() {
+        let mut coverage = Coverage::new(2 * PRG_ROM_PAGE_SIZE);
+        for offset in [0x0000, 0x0001, 0x0002, 0x0010, 0x3fff, 0x4000] {
+            coverage.record(offset);
+        }
+
+        assert_eq!(
+            coverage.report(),
+            "PRG ROM: 6/32768 bytes executed (0.0%)\n\
+             Bank 0: 5/16384 bytes executed (0.0%)\n\
+             \x20 0000-0002\n\
+             \x20 0010-0010\n\
+             \x20 3fff-3fff\n\
+             Bank 1: 1/16384 bytes executed (0.0%)\n\
+             \x20 0000-0000\n"
+        );
+    }
+
+    #[test]
+    fn test_report_percentages() {
+        let mut coverage = Coverage::new(PRG_ROM_PAGE_SIZE);
+        for offset in 0..PRG_ROM_PAGE_SIZE / 4 {
+            coverage.record(offset);
+        }
+        assert!(coverage
+            .report()
+            .starts_with("PRG ROM: 4096/16384 bytes executed (25.0%)\n"));
+        assert_eq!(
+            Coverage::new(0).report(),
+            "PRG ROM: 0/0 bytes executed (0.0%)\n"
+        );
+    }
+}
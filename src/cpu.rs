@@ -1,7 +1,40 @@
 use crate::bus::Bus;
 use crate::opcodes;
-use std::collections::HashMap;
 
+const STACK: u16 = 0x0100;
+const STACK_RESET: u8 = 0xfd;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Interrupt {
+    Nmi,
+    Irq,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Step {
+    Instruction(StepResult),
+    Interrupt(Interrupt),
+    Halted,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StepResult {
+    pub opcode: u8,
+    pub mnemonic: &'static str,
+    pub len: u8,
+    pub operand_bytes: [u8; 2],
+    pub mode: AddressingMode,
+    pub cycles: u64,
+    pub program_counter: u16,
+}
+
+impl StepResult {
+    pub fn operands(&self) -> &[u8] {
+        &self.operand_bytes[..self.len as usize - 1]
+    }
+}
+
+/// CPU is `Send`: it owns the bus and cartridge, so a frontend can run it on its own thread.
 pub struct CPU {
     pub register_a: u8,
     pub register_x: u8,
@@ -9,10 +42,21 @@ pub struct CPU {
     pub status: u8,
     pub program_counter: u16,
     pub stack_pointer: u8,
+    pub cycles: u64,
+    pub halt_on_brk: bool,
     pub bus: Bus,
+    halted: bool,
+    irq_line: bool,
+    nmi_line: bool,
+    nmi_pending: bool,
 }
 
-#[derive(Debug, PartialEq)]
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<CPU>();
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 #[allow(non_camel_case_types)]
 pub enum AddressingMode {
     Implied,
@@ -68,6 +112,10 @@ impl Mem for CPU {
     }
 }
 
+fn page_differs(addr1: u16, addr2: u16) -> bool {
+    addr1 & 0xff00 != addr2 & 0xff00
+}
+
 impl CPU {
     pub fn new(bus: Bus) -> Self {
         CPU {
@@ -76,18 +124,24 @@ impl CPU {
             register_y: 0,
             status: 0,
             program_counter: 0,
-            stack_pointer: 0xFD,
+            stack_pointer: STACK_RESET,
+            cycles: 0,
+            halt_on_brk: false,
             bus,
+            halted: false,
+            irq_line: false,
+            nmi_line: false,
+            nmi_pending: false,
         }
     }
 
     fn stack_pop(&mut self) -> u8 {
         self.stack_pointer = self.stack_pointer.wrapping_add(1);
-        self.mem_read(0x0100 + self.stack_pointer as u16)
+        self.mem_read(STACK + self.stack_pointer as u16)
     }
 
     fn stack_push(&mut self, data: u8) {
-        self.mem_write(0x0100 + self.stack_pointer as u16, data);
+        self.mem_write(STACK + self.stack_pointer as u16, data);
         self.stack_pointer = self.stack_pointer.wrapping_sub(1);
     }
 
@@ -104,6 +158,12 @@ impl CPU {
         self.stack_push(lo);
     }
 
+    fn mem_read_zero_page_u16(&self, ptr: u8) -> u16 {
+        let lo = self.mem_read(ptr as u16) as u16;
+        let hi = self.mem_read(ptr.wrapping_add(1) as u16) as u16;
+        (hi << 8) | lo
+    }
+
     fn get_operand_address(&self, mode: &AddressingMode) -> u16 {
         match mode {
             AddressingMode::Implied => {
@@ -147,18 +207,25 @@ impl CPU {
 
             AddressingMode::Indirect => {
                 let base = self.mem_read_u16(self.program_counter);
-                self.mem_read_u16(base)
+                // The 6502 does not carry into the high byte, so ($xxFF) wraps within the page
+                if base & 0x00ff == 0x00ff {
+                    let lo = self.mem_read(base) as u16;
+                    let hi = self.mem_read(base & 0xff00) as u16;
+                    (hi << 8) | lo
+                } else {
+                    self.mem_read_u16(base)
+                }
             }
 
             AddressingMode::Indirect_X => {
                 let base = self.mem_read(self.program_counter);
                 let ptr = base.wrapping_add(self.register_x);
-                self.mem_read_u16(ptr as u16)
+                self.mem_read_zero_page_u16(ptr)
             }
 
             AddressingMode::Indirect_Y => {
                 let base = self.mem_read(self.program_counter);
-                let deref_base = self.mem_read_u16(base as u16);
+                let deref_base = self.mem_read_zero_page_u16(base);
                 deref_base.wrapping_add(self.register_y as u16)
             }
 
@@ -168,6 +235,39 @@ impl CPU {
         }
     }
 
+    fn interrupt(&mut self, vector: u16) {
+        self.stack_push_u16(self.program_counter);
+        self.stack_push(self.status & !0b00010000 | 0b00100000);
+        self.status |= 0b00000100;
+        self.cycles += 7;
+        self.program_counter = self.mem_read_u16(vector);
+    }
+
+    fn page_crossed(&self, mode: &AddressingMode, operands: [u8; 2]) -> bool {
+        // Peek so the operand fetch done by the instruction itself is the only recorded read
+        match mode {
+            AddressingMode::Absolute_X => {
+                let base = u16::from_le_bytes(operands);
+                page_differs(base, base.wrapping_add(self.register_x as u16))
+            }
+
+            AddressingMode::Absolute_Y => {
+                let base = u16::from_le_bytes(operands);
+                page_differs(base, base.wrapping_add(self.register_y as u16))
+            }
+
+            AddressingMode::Indirect_Y => {
+                let ptr = operands[0];
+                let lo = self.bus.peek_cpu(ptr as u16).unwrap_or(0);
+                let hi = self.bus.peek_cpu(ptr.wrapping_add(1) as u16).unwrap_or(0);
+                let deref_base = u16::from_le_bytes([lo, hi]);
+                page_differs(deref_base, deref_base.wrapping_add(self.register_y as u16))
+            }
+
+            _ => false,
+        }
+    }
+
     fn update_zero_and_negative_flags(&mut self, result: u8) {
         if result == 0 {
             self.status |= 0b00000010;
@@ -182,6 +282,10 @@ impl CPU {
         }
     }
 
+    fn subtract_from_register_a(&mut self, value: u8) {
+        self.add_to_register_a(value.wrapping_neg().wrapping_sub(1));
+    }
+
     fn set_register_a(&mut self, value: u8) {
         self.register_a = value;
         self.update_zero_and_negative_flags(self.register_a);
@@ -189,7 +293,9 @@ impl CPU {
 
     fn add_to_register_a(&mut self, value: u8) {
         let carry = self.status & 0b00000001;
-        let (result, carry_flag) = self.register_a.overflowing_add(value + carry);
+        let sum = self.register_a as u16 + value as u16 + carry as u16;
+        let carry_flag = sum > 0xff;
+        let result = sum as u8;
         let overflow_flag = (self.register_a & 0b10000000) == (value & 0b10000000)
             && (value & 0b10000000) != (result & 0b10000000);
 
@@ -211,6 +317,10 @@ impl CPU {
     fn branch(&mut self, condition: bool) {
         if condition {
             let addr = self.get_operand_address(&AddressingMode::Relative);
+            self.cycles += 1;
+            if page_differs(self.program_counter + 1, addr) {
+                self.cycles += 1;
+            }
             self.program_counter = addr;
         }
     }
@@ -218,6 +328,10 @@ impl CPU {
     fn compare(&mut self, mode: &AddressingMode, target: u8) {
         let addr = self.get_operand_address(mode);
         let value = self.mem_read(addr);
+        self.compare_value(target, value);
+    }
+
+    fn compare_value(&mut self, target: u8, value: u8) {
         let result = target.wrapping_sub(value);
         let carry_flag = target >= value;
 
@@ -242,7 +356,7 @@ impl CPU {
         self.set_register_a(self.register_a & value);
     }
 
-    fn asl(&mut self, mode: &AddressingMode) {
+    fn asl(&mut self, mode: &AddressingMode) -> u8 {
         let (result, carry_flag) = if mode == &AddressingMode::Accumulator {
             let (result, carry_flag) = self.register_a.overflowing_mul(2);
             self.register_a = result;
@@ -262,6 +376,7 @@ impl CPU {
         };
 
         self.update_zero_and_negative_flags(result);
+        result
     }
 
     fn bcc(&mut self, _mode: &AddressingMode) {
@@ -312,6 +427,19 @@ impl CPU {
         self.branch(self.status & 0b10000000 != 0b10000000);
     }
 
+    fn brk(&mut self, _mode: &AddressingMode) {
+        if self.halt_on_brk {
+            self.program_counter -= 1;
+            self.halt();
+            return;
+        }
+
+        self.stack_push_u16(self.program_counter + 1);
+        self.stack_push(self.status | 0b00110000);
+        self.status |= 0b00000100;
+        self.program_counter = self.mem_read_u16(0xfffe);
+    }
+
     fn bvc(&mut self, _mode: &AddressingMode) {
         self.branch(self.status & 0b01000000 != 0b01000000);
     }
@@ -348,12 +476,13 @@ impl CPU {
         self.compare(mode, self.register_y);
     }
 
-    fn dec(&mut self, mode: &AddressingMode) {
+    fn dec(&mut self, mode: &AddressingMode) -> u8 {
         let addr = self.get_operand_address(mode);
         let value = self.mem_read(addr);
         let result = value.wrapping_sub(1);
         self.mem_write(addr, result);
         self.update_zero_and_negative_flags(result);
+        result
     }
 
     fn dex(&mut self, _mode: &AddressingMode) {
@@ -372,12 +501,13 @@ impl CPU {
         self.set_register_a(self.register_a ^ value);
     }
 
-    fn inc(&mut self, mode: &AddressingMode) {
+    fn inc(&mut self, mode: &AddressingMode) -> u8 {
         let addr = self.get_operand_address(mode);
         let value = self.mem_read(addr);
         let result = value.wrapping_add(1);
         self.mem_write(addr, result);
         self.update_zero_and_negative_flags(result);
+        result
     }
 
     fn inx(&mut self, _mode: &AddressingMode) {
@@ -422,7 +552,7 @@ impl CPU {
         self.update_zero_and_negative_flags(self.register_y);
     }
 
-    fn lsr(&mut self, mode: &AddressingMode) {
+    fn lsr(&mut self, mode: &AddressingMode) -> u8 {
         let (result, carry_flag) = if mode == &AddressingMode::Accumulator {
             let result = self.register_a / 2;
             let carry_flag = self.register_a & 0b00000001 == 0b00000001;
@@ -444,6 +574,7 @@ impl CPU {
         };
 
         self.update_zero_and_negative_flags(result);
+        result
     }
 
     fn nop(&mut self, _mode: &AddressingMode) {
@@ -474,7 +605,7 @@ impl CPU {
         self.status = value & !0b00010000 | 0b00100000;
     }
 
-    fn rol(&mut self, mode: &AddressingMode) {
+    fn rol(&mut self, mode: &AddressingMode) -> u8 {
         let (result, carry_flag) = if mode == &AddressingMode::Accumulator {
             let (result, carry_flag) = self.register_a.overflowing_mul(2);
             let result = result | (self.status & 0b00000001);
@@ -496,9 +627,10 @@ impl CPU {
         };
 
         self.update_zero_and_negative_flags(result);
+        result
     }
 
-    fn ror(&mut self, mode: &AddressingMode) {
+    fn ror(&mut self, mode: &AddressingMode) -> u8 {
         let (result, carry_flag) = if mode == &AddressingMode::Accumulator {
             let result = self.register_a / 2;
             let result = result | (self.status & 0b00000001) << 7;
@@ -522,6 +654,7 @@ impl CPU {
         };
 
         self.update_zero_and_negative_flags(result);
+        result
     }
 
     fn rti(&mut self, _mode: &AddressingMode) {
@@ -538,7 +671,7 @@ impl CPU {
     fn sbc(&mut self, mode: &AddressingMode) {
         let addr = self.get_operand_address(mode);
         let value = self.mem_read(addr);
-        self.add_to_register_a(value.wrapping_neg().wrapping_sub(1));
+        self.subtract_from_register_a(value);
     }
 
     fn sec(&mut self, _mode: &AddressingMode) {
@@ -597,6 +730,149 @@ impl CPU {
         self.update_zero_and_negative_flags(self.register_a);
     }
 
+    /* Unofficial opcodes */
+
+    fn ahx(&mut self, mode: &AddressingMode) {
+        self.store_and_high_byte(mode, self.register_a & self.register_x, self.register_y);
+    }
+
+    fn alr(&mut self, mode: &AddressingMode) {
+        self.and(mode);
+        self.lsr(&AddressingMode::Accumulator);
+    }
+
+    fn anc(&mut self, mode: &AddressingMode) {
+        self.and(mode);
+
+        if self.status & 0b10000000 != 0 {
+            self.status |= 0b00000001
+        } else {
+            self.status &= !0b00000001
+        };
+    }
+
+    fn arr(&mut self, mode: &AddressingMode) {
+        self.and(mode);
+        self.ror(&AddressingMode::Accumulator);
+
+        let bit_6 = self.register_a & 0b01000000 != 0;
+        let bit_5 = self.register_a & 0b00100000 != 0;
+
+        if bit_6 {
+            self.status |= 0b00000001
+        } else {
+            self.status &= !0b00000001
+        };
+
+        if bit_6 ^ bit_5 {
+            self.status |= 0b01000000
+        } else {
+            self.status &= !0b01000000
+        };
+    }
+
+    fn axs(&mut self, mode: &AddressingMode) {
+        let addr = self.get_operand_address(mode);
+        let value = self.mem_read(addr);
+        let target = self.register_a & self.register_x;
+        let result = target.wrapping_sub(value);
+
+        if target >= value {
+            self.status |= 0b00000001
+        } else {
+            self.status &= !0b00000001
+        };
+
+        self.register_x = result;
+        self.update_zero_and_negative_flags(self.register_x);
+    }
+
+    fn dcp(&mut self, mode: &AddressingMode) {
+        let value = self.dec(mode);
+        self.compare_value(self.register_a, value);
+    }
+
+    fn isb(&mut self, mode: &AddressingMode) {
+        let value = self.inc(mode);
+        self.subtract_from_register_a(value);
+    }
+
+    fn jam(&mut self, _mode: &AddressingMode) {
+        self.program_counter -= 1;
+        self.halt();
+    }
+
+    fn las(&mut self, mode: &AddressingMode) {
+        let addr = self.get_operand_address(mode);
+        let value = self.mem_read(addr) & self.stack_pointer;
+        self.register_a = value;
+        self.register_x = value;
+        self.stack_pointer = value;
+        self.update_zero_and_negative_flags(value);
+    }
+
+    fn lax(&mut self, mode: &AddressingMode) {
+        self.lda(mode);
+        self.tax();
+    }
+
+    fn lxa(&mut self, mode: &AddressingMode) {
+        // Unstable on real hardware, the "magic" constant is assumed to be 0xff
+        self.lda(mode);
+        self.tax();
+    }
+
+    fn rla(&mut self, mode: &AddressingMode) {
+        let value = self.rol(mode);
+        self.set_register_a(self.register_a & value);
+    }
+
+    fn rra(&mut self, mode: &AddressingMode) {
+        let value = self.ror(mode);
+        self.add_to_register_a(value);
+    }
+
+    fn sax(&mut self, mode: &AddressingMode) {
+        let addr = self.get_operand_address(mode);
+        self.mem_write(addr, self.register_a & self.register_x);
+    }
+
+    fn shx(&mut self, mode: &AddressingMode) {
+        self.store_and_high_byte(mode, self.register_x, self.register_y);
+    }
+
+    fn shy(&mut self, mode: &AddressingMode) {
+        self.store_and_high_byte(mode, self.register_y, self.register_x);
+    }
+
+    fn slo(&mut self, mode: &AddressingMode) {
+        let value = self.asl(mode);
+        self.set_register_a(self.register_a | value);
+    }
+
+    fn sre(&mut self, mode: &AddressingMode) {
+        let value = self.lsr(mode);
+        self.set_register_a(self.register_a ^ value);
+    }
+
+    fn tas(&mut self, mode: &AddressingMode) {
+        self.stack_pointer = self.register_a & self.register_x;
+        self.store_and_high_byte(mode, self.stack_pointer, self.register_y);
+    }
+
+    fn xaa(&mut self, mode: &AddressingMode) {
+        // Unstable on real hardware, the "magic" constant is assumed to be 0xff
+        let addr = self.get_operand_address(mode);
+        let value = self.mem_read(addr);
+        self.set_register_a(self.register_x & value);
+    }
+
+    fn store_and_high_byte(&mut self, mode: &AddressingMode, value: u8, index: u8) {
+        let addr = self.get_operand_address(mode);
+        let hi = (addr.wrapping_sub(index as u16) >> 8) as u8;
+        self.mem_write(addr, value & hi.wrapping_add(1));
+    }
+
     pub fn load_and_run(&mut self, program: Vec<u8>) {
         self.load(program);
         self.reset();
@@ -607,7 +883,6 @@ impl CPU {
         for i in 0..(program.len() as u16) {
             self.mem_write(0x0600 + i, program[i as usize]);
         }
-        self.mem_write_u16(0xfffc, 0x0600);
     }
 
     pub fn reset(&mut self) {
@@ -615,386 +890,1330 @@ impl CPU {
         self.register_x = 0;
         self.register_y = 0;
         self.status = 0;
+        // The reset sequence takes 7 cycles before the first instruction is fetched
+        self.cycles = 7;
+        self.nmi_pending = false;
+        self.halted = false;
 
         self.program_counter = self.mem_read_u16(0xFFFC);
     }
 
+    pub fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    pub fn resume(&mut self) {
+        // BRK and JAM halt with PC on the opcode, so resuming runs it again
+        self.halted = false;
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
+
+    pub fn set_nmi_line(&mut self, asserted: bool) {
+        if asserted && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = asserted;
+    }
+
     pub fn run(&mut self) {
         self.run_with_callback(|_| {});
     }
 
-    pub fn run_with_callback<F>(&mut self, mut callback: F)
-    where
-        F: FnMut(&mut CPU),
-    {
-        let ref opcodes: HashMap<u8, &'static opcodes::OpCode> = *opcodes::OPCODES_MAP;
+    pub fn step(&mut self) -> Step {
+        if self.halted {
+            return Step::Halted;
+        }
+
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            self.interrupt(0xfffa);
+            return Step::Interrupt(Interrupt::Nmi);
+        } else if self.irq_line && self.status & 0b00000100 == 0 {
+            self.interrupt(0xfffe);
+            return Step::Interrupt(Interrupt::Irq);
+        }
+
+        let start_cycles = self.cycles;
+        let code = self.mem_read(self.program_counter);
+        let opcode = opcodes::OPCODES_MAP.get(&code).unwrap();
+        self.bus.record_execute(self.program_counter, opcode.len);
+        let mut operand_bytes = [0; 2];
+        for i in 1..opcode.len as u16 {
+            operand_bytes[i as usize - 1] = self
+                .bus
+                .peek_cpu(self.program_counter.wrapping_add(i))
+                .unwrap_or(0);
+        }
+        self.program_counter += 1;
+        let program_counter_state = self.program_counter;
+        let page_crossed =
+            opcode.page_cross_cycle && self.page_crossed(&opcode.mode, operand_bytes);
+
+        match code {
+            /* ADC */
+            0x69 | 0x65 | 0x75 | 0x6d | 0x7d | 0x79 | 0x61 | 0x71 => self.adc(&opcode.mode),
+
+            /* AND */
+            0x29 | 0x25 | 0x35 | 0x2d | 0x3d | 0x39 | 0x21 | 0x31 => self.and(&opcode.mode),
+
+            /* ASL */
+            0x0a | 0x06 | 0x16 | 0x0e | 0x1e => {
+                self.asl(&opcode.mode);
+            }
+
+            /* BCC */
+            0x90 => self.bcc(&opcode.mode),
+
+            /* BCS */
+            0xb0 => self.bcs(&opcode.mode),
 
-        loop {
-            let code = self.mem_read(self.program_counter);
-            self.program_counter += 1;
-            let program_counter_state = self.program_counter;
+            /* BEQ */
+            0xf0 => self.beq(&opcode.mode),
 
-            let opcode = opcodes.get(&code).unwrap();
+            /* BIT */
+            0x24 | 0x2c => self.bit(&opcode.mode),
 
-            match code {
-                /* ADC */
-                0x69 | 0x65 | 0x75 | 0x6d | 0x7d | 0x79 | 0x61 | 0x71 => self.adc(&opcode.mode),
+            /* BMI */
+            0x30 => self.bmi(&opcode.mode),
 
-                /* AND */
-                0x29 | 0x25 | 0x35 | 0x2d | 0x3d | 0x39 | 0x21 | 0x31 => self.and(&opcode.mode),
+            /* BNE */
+            0xd0 => self.bne(&opcode.mode),
 
-                /* ASL */
-                0x0a | 0x06 | 0x16 | 0x0e | 0x1e => self.asl(&opcode.mode),
+            /* BPL */
+            0x10 => self.bpl(&opcode.mode),
 
-                /* BCC */
-                0x90 => self.bcc(&opcode.mode),
+            /* BRK */
+            0x00 => self.brk(&opcode.mode),
 
-                /* BCS */
-                0xb0 => self.bcs(&opcode.mode),
+            /* BVC */
+            0x50 => self.bvc(&opcode.mode),
 
-                /* BEQ */
-                0xf0 => self.beq(&opcode.mode),
+            /* BVS */
+            0x70 => self.bvs(&opcode.mode),
 
-                /* BIT */
-                0x24 | 0x2c => self.bit(&opcode.mode),
+            /* CLC */
+            0x18 => self.clc(&opcode.mode),
 
-                /* BMI */
-                0x30 => self.bmi(&opcode.mode),
+            /* CLD */
+            0xd8 => self.cld(&opcode.mode),
 
-                /* BNE */
-                0xd0 => self.bne(&opcode.mode),
+            /* CLI */
+            0x58 => self.cli(&opcode.mode),
 
-                /* BPL */
-                0x10 => self.bpl(&opcode.mode),
+            /* CLV */
+            0xb8 => self.clv(&opcode.mode),
 
-                /* BRK */
-                0x00 => return,
+            /* CMP */
+            0xc9 | 0xc5 | 0xd5 | 0xcd | 0xdd | 0xd9 | 0xc1 | 0xd1 => self.cmp(&opcode.mode),
 
-                /* BVC */
-                0x50 => self.bvc(&opcode.mode),
+            /* CPX */
+            0xe0 | 0xe4 | 0xec => self.cpx(&opcode.mode),
 
-                /* BVS */
-                0x70 => self.bvs(&opcode.mode),
+            /* CPY */
+            0xc0 | 0xc4 | 0xcc => self.cpy(&opcode.mode),
 
-                /* CLC */
-                0x18 => self.clc(&opcode.mode),
+            /* DEC */
+            0xc6 | 0xd6 | 0xce | 0xde => {
+                self.dec(&opcode.mode);
+            }
 
-                /* CLD */
-                0xd8 => self.cld(&opcode.mode),
+            /* DEX */
+            0xca => self.dex(&opcode.mode),
 
-                /* CLI */
-                0x58 => self.cli(&opcode.mode),
+            /* DEY */
+            0x88 => self.dey(&opcode.mode),
 
-                /* CLV */
-                0xb8 => self.clv(&opcode.mode),
+            /* EOR */
+            0x49 | 0x45 | 0x55 | 0x4d | 0x5d | 0x59 | 0x41 | 0x51 => self.eor(&opcode.mode),
 
-                /* CMP */
-                0xc9 | 0xc5 | 0xd5 | 0xcd | 0xdd | 0xd9 | 0xc1 | 0xd1 => self.cmp(&opcode.mode),
+            /* INC */
+            0xe6 | 0xf6 | 0xee | 0xfe => {
+                self.inc(&opcode.mode);
+            }
 
-                /* CPX */
-                0xe0 | 0xe4 | 0xec => self.cpx(&opcode.mode),
+            /* INX */
+            0xe8 => self.inx(&opcode.mode),
 
-                /* CPY */
-                0xc0 | 0xc4 | 0xcc => self.cpy(&opcode.mode),
+            /* INY */
+            0xc8 => self.iny(&opcode.mode),
 
-                /* DEC */
-                0xc6 | 0xd6 | 0xce | 0xde => self.dec(&opcode.mode),
+            /* JMP */
+            0x4c | 0x6c => self.jmp(&opcode.mode),
 
-                /* DEX */
-                0xca => self.dex(&opcode.mode),
+            /* JSR */
+            0x20 => self.jsr(&opcode.mode),
 
-                /* DEY */
-                0x88 => self.dey(&opcode.mode),
+            /* LDA */
+            0xa9 | 0xa5 | 0xb5 | 0xad | 0xbd | 0xb9 | 0xa1 | 0xb1 => self.lda(&opcode.mode),
 
-                /* EOR */
-                0x49 | 0x45 | 0x55 | 0x4d | 0x5d | 0x59 | 0x41 | 0x51 => self.eor(&opcode.mode),
+            /* LDX */
+            0xa2 | 0xa6 | 0xb6 | 0xae | 0xbe => self.ldx(&opcode.mode),
 
-                /* INC */
-                0xe6 | 0xf6 | 0xee | 0xfe => self.inc(&opcode.mode),
+            /* LDY */
+            0xa0 | 0xa4 | 0xb4 | 0xac | 0xbc => self.ldy(&opcode.mode),
 
-                /* INX */
-                0xe8 => self.inx(&opcode.mode),
+            /* LSR */
+            0x4a | 0x46 | 0x56 | 0x4e | 0x5e => {
+                self.lsr(&opcode.mode);
+            }
 
-                /* INY */
-                0xc8 => self.iny(&opcode.mode),
+            /* NOP */
+            0xea => self.nop(&opcode.mode),
 
-                /* JMP */
-                0x4c | 0x6c => self.jmp(&opcode.mode),
+            /* ORA */
+            0x09 | 0x05 | 0x15 | 0x0d | 0x1d | 0x19 | 0x01 | 0x11 => self.ora(&opcode.mode),
 
-                /* JSR */
-                0x20 => self.jsr(&opcode.mode),
+            /* PHA */
+            0x48 => self.pha(&opcode.mode),
 
-                /* LDA */
-                0xa9 | 0xa5 | 0xb5 | 0xad | 0xbd | 0xb9 | 0xa1 | 0xb1 => self.lda(&opcode.mode),
+            /* PHP */
+            0x08 => self.php(&opcode.mode),
 
-                /* LDX */
-                0xa2 | 0xa6 | 0xb6 | 0xae | 0xbe => self.ldx(&opcode.mode),
+            /* PLA */
+            0x68 => self.pla(&opcode.mode),
 
-                /* LDY */
-                0xa0 | 0xa4 | 0xb4 | 0xac | 0xbc => self.ldy(&opcode.mode),
+            /* PLP */
+            0x28 => self.plp(&opcode.mode),
 
-                /* LSR */
-                0x4a | 0x46 | 0x56 | 0x4e | 0x5e => self.lsr(&opcode.mode),
+            /* ROL */
+            0x2a | 0x26 | 0x36 | 0x2e | 0x3e => {
+                self.rol(&opcode.mode);
+            }
 
-                /* NOP */
-                0xea => self.nop(&opcode.mode),
+            /* ROR */
+            0x6a | 0x66 | 0x76 | 0x6e | 0x7e => {
+                self.ror(&opcode.mode);
+            }
+
+            /* RTI */
+            0x40 => self.rti(&opcode.mode),
 
-                /* ORA */
-                0x09 | 0x05 | 0x15 | 0x0d | 0x1d | 0x19 | 0x01 | 0x11 => self.ora(&opcode.mode),
+            /* RTS */
+            0x60 => self.rts(&opcode.mode),
 
-                /* PHA */
-                0x48 => self.pha(&opcode.mode),
+            /* SBC */
+            0xe9 | 0xe5 | 0xf5 | 0xed | 0xfd | 0xf9 | 0xe1 | 0xf1 => self.sbc(&opcode.mode),
 
-                /* PHP */
-                0x08 => self.php(&opcode.mode),
+            /* SEC */
+            0x38 => self.sec(&opcode.mode),
 
-                /* PLA */
-                0x68 => self.pla(&opcode.mode),
+            /* SED */
+            0xf8 => self.sed(&opcode.mode),
 
-                /* PLP */
-                0x28 => self.plp(&opcode.mode),
+            /* SEI */
+            0x78 => self.sei(&opcode.mode),
 
-                /* ROL */
-                0x2a | 0x26 | 0x36 | 0x2e | 0x3e => self.rol(&opcode.mode),
+            /* STA */
+            0x85 | 0x95 | 0x8d | 0x9d | 0x99 | 0x81 | 0x91 => self.sta(&opcode.mode),
 
-                /* ROR */
-                0x6a | 0x66 | 0x76 | 0x6e | 0x7e => self.ror(&opcode.mode),
+            /* STX */
+            0x86 | 0x96 | 0x8e => self.stx(&opcode.mode),
 
-                /* RTI */
-                0x40 => self.rti(&opcode.mode),
+            /* STY */
+            0x84 | 0x94 | 0x8c => self.sty(&opcode.mode),
 
-                /* RTS */
-                0x60 => self.rts(&opcode.mode),
+            /* TAX */
+            0xaa => self.tax(),
 
-                /* SBC */
-                0xe9 | 0xe5 | 0xf5 | 0xed | 0xfd | 0xf9 | 0xe1 | 0xf1 => self.sbc(&opcode.mode),
+            /* TAY */
+            0xa8 => self.tay(),
 
-                /* SEC */
-                0x38 => self.sec(&opcode.mode),
+            /* TSX */
+            0xba => self.tsx(),
 
-                /* SED */
-                0xf8 => self.sed(&opcode.mode),
+            /* TXA */
+            0x8a => self.txa(),
 
-                /* SEI */
-                0x78 => self.sei(&opcode.mode),
+            /* TXS */
+            0x9a => self.txs(),
 
-                /* STA */
-                0x85 | 0x95 | 0x8d | 0x9d | 0x99 | 0x81 | 0x91 => self.sta(&opcode.mode),
+            /* TYA */
+            0x98 => self.tya(),
 
-                /* STX */
-                0x86 | 0x96 | 0x8e => self.stx(&opcode.mode),
+            /* Unofficial opcodes */
 
-                /* STY */
-                0x84 | 0x94 | 0x8c => self.sty(&opcode.mode),
+            /* *AHX */
+            0x9f | 0x93 => self.ahx(&opcode.mode),
 
-                /* TAX */
-                0xaa => self.tax(),
+            /* *ALR */
+            0x4b => self.alr(&opcode.mode),
 
-                /* TAY */
-                0xa8 => self.tay(),
+            /* *ANC */
+            0x0b | 0x2b => self.anc(&opcode.mode),
 
-                /* TSX */
-                0xba => self.tsx(),
+            /* *ARR */
+            0x6b => self.arr(&opcode.mode),
 
-                /* TXA */
-                0x8a => self.txa(),
+            /* *AXS */
+            0xcb => self.axs(&opcode.mode),
 
-                /* TXS */
-                0x9a => self.txs(),
+            /* *DCP */
+            0xc7 | 0xd7 | 0xcf | 0xdf | 0xdb | 0xc3 | 0xd3 => self.dcp(&opcode.mode),
 
-                /* TYA */
-                0x98 => self.tya(),
+            /* *ISB */
+            0xe7 | 0xf7 | 0xef | 0xff | 0xfb | 0xe3 | 0xf3 => self.isb(&opcode.mode),
 
-                _ => todo!(""),
+            /* *JAM */
+            0x02 | 0x12 | 0x22 | 0x32 | 0x42 | 0x52 | 0x62 | 0x72 | 0x92 | 0xb2 | 0xd2 | 0xf2 => {
+                self.jam(&opcode.mode)
             }
 
-            if program_counter_state == self.program_counter {
-                self.program_counter += (opcode.len - 1) as u16;
+            /* *LAS */
+            0xbb => self.las(&opcode.mode),
+
+            /* *LAX */
+            0xa7 | 0xb7 | 0xaf | 0xbf | 0xa3 | 0xb3 => self.lax(&opcode.mode),
+
+            /* *LXA */
+            0xab => self.lxa(&opcode.mode),
+
+            /* *NOP */
+            0x1a | 0x3a | 0x5a | 0x7a | 0xda | 0xfa | 0x80 | 0x82 | 0x89 | 0xc2 | 0xe2 | 0x04
+            | 0x44 | 0x64 | 0x14 | 0x34 | 0x54 | 0x74 | 0xd4 | 0xf4 | 0x0c | 0x1c | 0x3c | 0x5c
+            | 0x7c | 0xdc | 0xfc => self.nop(&opcode.mode),
+
+            /* *RLA */
+            0x27 | 0x37 | 0x2f | 0x3f | 0x3b | 0x23 | 0x33 => self.rla(&opcode.mode),
+
+            /* *RRA */
+            0x67 | 0x77 | 0x6f | 0x7f | 0x7b | 0x63 | 0x73 => self.rra(&opcode.mode),
+
+            /* *SAX */
+            0x87 | 0x97 | 0x8f | 0x83 => self.sax(&opcode.mode),
+
+            /* *SBC */
+            0xeb => self.sbc(&opcode.mode),
+
+            /* *SHX */
+            0x9e => self.shx(&opcode.mode),
+
+            /* *SHY */
+            0x9c => self.shy(&opcode.mode),
+
+            /* *SLO */
+            0x07 | 0x17 | 0x0f | 0x1f | 0x1b | 0x03 | 0x13 => self.slo(&opcode.mode),
+
+            /* *SRE */
+            0x47 | 0x57 | 0x4f | 0x5f | 0x5b | 0x43 | 0x53 => self.sre(&opcode.mode),
+
+            /* *TAS */
+            0x9b => self.tas(&opcode.mode),
+
+            /* *XAA */
+            0x8b => self.xaa(&opcode.mode),
+        }
+
+        if self.halted {
+            return Step::Halted;
+        }
+
+        if program_counter_state == self.program_counter {
+            self.program_counter += (opcode.len - 1) as u16;
+        }
+
+        self.cycles += opcode.cycles as u64;
+        if page_crossed {
+            self.cycles += 1;
+        }
+
+        Step::Instruction(StepResult {
+            opcode: code,
+            mnemonic: opcode.mnemonic,
+            len: opcode.len,
+            operand_bytes,
+            mode: opcode.mode,
+            cycles: self.cycles - start_cycles,
+            program_counter: self.program_counter,
+        })
+    }
+
+    pub fn run_for_cycles(&mut self, cycles: u64) -> u64 {
+        let start_cycles = self.cycles;
+        while !self.halted && self.cycles - start_cycles < cycles {
+            self.step();
+        }
+        self.cycles - start_cycles
+    }
+
+    pub fn run_until_pc(&mut self, addr: u16) -> bool {
+        while self.program_counter != addr {
+            if self.step() == Step::Halted {
+                return false;
             }
+        }
+        true
+    }
 
-            callback(self);
+    pub fn run_with_callback<F>(&mut self, mut callback: F)
+    where
+        F: FnMut(&mut CPU),
+    {
+        while !self.halted {
+            if let Step::Instruction(_) = self.step() {
+                callback(self);
+            }
         }
     }
 }
 
-// #[cfg(test)]
-// mod test {
-//     use super::*;
-//
-//     /* AND */
-//     #[test]
-//     fn test_and() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0x29, 0x0C, 0x00]);
-//         cpu.reset();
-//         cpu.register_a = 0x0A;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x08);
-//         assert_eq!(cpu.status, 0);
-//     }
-//
-//     /* EOR */
-//     #[test]
-//     fn test_eor() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0x49, 0x0C, 0x00]);
-//         cpu.reset();
-//         cpu.register_a = 0x0A;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x06);
-//         assert_eq!(cpu.status, 0);
-//     }
-//
-//     /* INX */
-//     #[test]
-//     fn test_inx_overflow() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xe8, 0xe8, 0x00]);
-//         cpu.reset();
-//         cpu.register_x = 0xff;
-//         cpu.run();
-//         assert_eq!(cpu.register_x, 1);
-//     }
-//
-//     /* LDA */
-//     #[test]
-//     fn test_lda_immediate() {
-//         let mut cpu = CPU::new();
-//         cpu.load_and_run(vec![0xa9, 0x05, 0x00]);
-//         assert_eq!(cpu.register_a, 0x05);
-//         assert_eq!(cpu.status, 0);
-//     }
-//
-//     #[test]
-//     fn test_lda_zero_flag() {
-//         let mut cpu = CPU::new();
-//         cpu.load_and_run(vec![0xa9, 0x00, 0x00]);
-//         assert_eq!(cpu.status, 0b00000010);
-//     }
-//
-//     #[test]
-//     fn test_lda_negative_flag() {
-//         let mut cpu = CPU::new();
-//         cpu.load_and_run(vec![0xa9, 0x80, 0x00]);
-//         assert_eq!(cpu.status, 0b10000000);
-//     }
-//
-//     #[test]
-//     fn test_lda_zero_page() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xa5, 0x10, 0x00]);
-//         cpu.reset();
-//         cpu.mem_write(0x10, 0x55);
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x55);
-//     }
-//
-//     #[test]
-//     fn test_lda_zero_page_x() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xb5, 0x10, 0x00]);
-//         cpu.reset();
-//         cpu.mem_write(0x11, 0x56);
-//         cpu.register_x = 0x01;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x56);
-//     }
-//
-//     #[test]
-//     fn test_lda_absolute() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xad, 0x10, 0x32, 0x00]);
-//         cpu.reset();
-//         cpu.mem_write(0x3210, 0x57);
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x57);
-//     }
-//
-//     #[test]
-//     fn test_lda_absolute_x() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xbd, 0x10, 0x32, 0x00]);
-//         cpu.reset();
-//         cpu.mem_write(0x3211, 0x58);
-//         cpu.register_x = 0x01;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x58);
-//     }
-//
-//     #[test]
-//     fn test_lda_absolute_y() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xb9, 0x10, 0x32, 0x00]);
-//         cpu.reset();
-//         cpu.mem_write(0x3220, 0x59);
-//         cpu.register_y = 0x10;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x59);
-//     }
-//
-//     #[test]
-//     fn test_lda_indirect_x() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xa1, 0x10, 0x00]);
-//         cpu.reset();
-//         cpu.mem_write_u16(0x11, 0x5432);
-//         cpu.mem_write(0x5432, 0x5a);
-//         cpu.register_x = 0x01;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x5a);
-//     }
-//
-//     #[test]
-//     fn test_lda_indirect_y() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xb1, 0x10, 0x00]);
-//         cpu.reset();
-//         cpu.mem_write_u16(0x10, 0x7654);
-//         cpu.mem_write(0x7664, 0x5b);
-//         cpu.register_y = 0x10;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x5b);
-//     }
-//
-//     /* ORA */
-//     #[test]
-//     fn test_ora() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0x09, 0x0C, 0x00]);
-//         cpu.reset();
-//         cpu.register_a = 0x0A;
-//         cpu.run();
-//         assert_eq!(cpu.register_a, 0x0E);
-//         assert_eq!(cpu.status, 0);
-//     }
-//
-//     /* STA */
-//     #[test]
-//     fn test_sta() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0x85, 0x10, 0x00]);
-//         cpu.reset();
-//         cpu.register_a = 0x5c;
-//         cpu.run();
-//         assert_eq!(cpu.mem_read(0x10), 0x5c);
-//     }
-//
-//     /* TAX */
-//     #[test]
-//     fn test_tax() {
-//         let mut cpu = CPU::new();
-//         cpu.load(vec![0xaa, 0x00]);
-//         cpu.reset();
-//         cpu.register_a = 10;
-//         cpu.run();
-//         assert_eq!(cpu.register_x, 10);
-//     }
-//
-//     /* Other */
-//     #[test]
-//     fn test_5_ops_working_together() {
-//         let mut cpu = CPU::new();
-//         cpu.load_and_run(vec![0xa9, 0xc0, 0xaa, 0xe8, 0x00]);
-//         assert_eq!(cpu.register_x, 0xc1);
-//     }
-// }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::test::test_rom;
+
+    fn test_cpu() -> CPU {
+        let mut cpu = CPU::new(Bus::new(test_rom()));
+        cpu.halt_on_brk = true;
+        cpu
+    }
+
+    /* AND */
+    #[test]
+    fn test_and() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x29, 0x0C, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x0A;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x08);
+        assert_eq!(cpu.status, 0);
+    }
+
+    /* BRK */
+    #[test]
+    fn test_brk_jumps_through_irq_vector() {
+        let mut cpu = CPU::new(Bus::new(test_rom()));
+        cpu.load(vec![0x00, 0xff, 0xa2, 0x05, 0x02]);
+        cpu.reset();
+        cpu.mem_write(0x0700, 0xa9);
+        cpu.mem_write(0x0701, 0x42);
+        cpu.mem_write(0x0702, 0x40);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x42);
+        assert_eq!(cpu.register_x, 0x05);
+        assert_eq!(cpu.mem_read_u16(0x01fc), 0x0602);
+        assert_eq!(cpu.mem_read(0x01fb), 0b00110000);
+        assert_eq!(cpu.stack_pointer, STACK_RESET);
+        assert_eq!(cpu.program_counter, 0x0604);
+    }
+
+    #[test]
+    fn test_halt_on_brk() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xa9, 0x01, 0x00, 0xa9, 0x02]);
+        assert_eq!(cpu.register_a, 0x01);
+        assert!(cpu.is_halted());
+        assert_eq!(cpu.program_counter, 0x0602);
+        assert_eq!(cpu.stack_pointer, STACK_RESET);
+        assert_eq!(cpu.cycles, 7 + 2);
+
+        cpu.resume();
+        cpu.run();
+        assert!(cpu.is_halted());
+        assert_eq!(cpu.program_counter, 0x0602);
+        assert_eq!(cpu.cycles, 7 + 2);
+    }
+
+    #[test]
+    fn test_halt_from_callback() {
+        let mut cpu = CPU::new(Bus::new(test_rom()));
+        cpu.load(vec![0xe8, 0x4c, 0x00, 0x06]);
+        cpu.reset();
+        cpu.run_with_callback(|cpu| {
+            if cpu.register_x == 3 {
+                cpu.halt();
+            }
+        });
+        assert_eq!(cpu.register_x, 3);
+        assert!(cpu.is_halted());
+    }
+
+    #[test]
+    fn test_resume_after_halt() {
+        let mut cpu = CPU::new(Bus::new(test_rom()));
+        cpu.load(vec![0xe8, 0x4c, 0x00, 0x06]);
+        cpu.reset();
+        cpu.run_with_callback(|cpu| {
+            if cpu.register_x == 3 {
+                cpu.halt();
+            }
+        });
+
+        cpu.resume();
+        assert!(!cpu.is_halted());
+        cpu.run_with_callback(|cpu| {
+            if cpu.register_x == 5 {
+                cpu.halt();
+            }
+        });
+        assert_eq!(cpu.register_x, 5);
+    }
+
+    /* EOR */
+    #[test]
+    fn test_eor() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x49, 0x0C, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x0A;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x06);
+        assert_eq!(cpu.status, 0);
+    }
+
+    /* INX */
+    #[test]
+    fn test_inx_overflow() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xe8, 0xe8, 0x00]);
+        cpu.reset();
+        cpu.register_x = 0xff;
+        cpu.run();
+        assert_eq!(cpu.register_x, 1);
+    }
+
+    /* IRQ */
+    #[test]
+    fn test_irq_is_serviced_between_instructions() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xea, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0700, 0xa9);
+        cpu.mem_write(0x0701, 0x42);
+        cpu.mem_write(0x0702, 0x00);
+        cpu.set_irq_line(true);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x42);
+        assert_eq!(cpu.status & 0b00000100, 0b00000100);
+        assert_eq!(cpu.stack_pointer, STACK_RESET - 3);
+        assert_eq!(cpu.mem_read_u16(0x01fc), 0x0600);
+        assert_eq!(cpu.mem_read(0x01fb), 0b00100000);
+        assert_eq!(cpu.cycles, 7 + 7 + 2);
+    }
+
+    #[test]
+    fn test_irq_is_masked_by_interrupt_disable_flag() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa9, 0x01, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0700, 0xa9);
+        cpu.mem_write(0x0701, 0x42);
+        cpu.mem_write(0x0702, 0x00);
+        cpu.status = 0b00000100;
+        cpu.set_irq_line(true);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x01);
+        assert_eq!(cpu.stack_pointer, STACK_RESET);
+    }
+
+    #[test]
+    fn test_rti_returns_from_irq_handler() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa2, 0x05, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0700, 0xa9);
+        cpu.mem_write(0x0701, 0x42);
+        cpu.mem_write(0x0702, 0x40);
+        cpu.set_irq_line(true);
+        cpu.run_with_callback(|cpu| cpu.set_irq_line(false));
+        assert_eq!(cpu.register_a, 0x42);
+        assert_eq!(cpu.register_x, 0x05);
+        assert_eq!(cpu.status & 0b00000100, 0);
+        assert_eq!(cpu.stack_pointer, STACK_RESET);
+    }
+
+    /* JMP */
+    #[test]
+    fn test_jmp_indirect() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x6c, 0x10, 0x02]);
+        cpu.reset();
+        cpu.mem_write_u16(0x0210, 0x0700);
+        cpu.mem_write(0x0700, 0xa9);
+        cpu.mem_write(0x0701, 0x42);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x42);
+    }
+
+    #[test]
+    fn test_jmp_indirect_wraps_within_page() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x6c, 0xff, 0x02]);
+        cpu.reset();
+        cpu.mem_write(0x02ff, 0x80);
+        cpu.mem_write(0x0200, 0x07);
+        cpu.mem_write(0x0300, 0x05);
+        cpu.mem_write(0x0780, 0xa9);
+        cpu.mem_write(0x0781, 0x42);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x42);
+        assert_eq!(cpu.program_counter, 0x0782);
+    }
+
+    /* LDA */
+    #[test]
+    fn test_lda_immediate() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xa9, 0x05, 0x00]);
+        assert_eq!(cpu.register_a, 0x05);
+        assert_eq!(cpu.status, 0);
+    }
+
+    #[test]
+    fn test_lda_zero_flag() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xa9, 0x00, 0x00]);
+        assert_eq!(cpu.status, 0b00000010);
+    }
+
+    #[test]
+    fn test_lda_negative_flag() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xa9, 0x80, 0x00]);
+        assert_eq!(cpu.status, 0b10000000);
+    }
+
+    #[test]
+    fn test_lda_zero_page() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa5, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x55);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x55);
+    }
+
+    #[test]
+    fn test_lda_zero_page_x() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xb5, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x11, 0x56);
+        cpu.register_x = 0x01;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x56);
+    }
+
+    #[test]
+    fn test_lda_absolute() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xad, 0x10, 0x02, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0210, 0x57);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x57);
+    }
+
+    #[test]
+    fn test_lda_absolute_x() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xbd, 0x10, 0x02, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0211, 0x58);
+        cpu.register_x = 0x01;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x58);
+    }
+
+    #[test]
+    fn test_lda_absolute_y() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xb9, 0x10, 0x02, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0220, 0x59);
+        cpu.register_y = 0x10;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x59);
+    }
+
+    #[test]
+    fn test_lda_indirect_x() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa1, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write_u16(0x11, 0x0432);
+        cpu.mem_write(0x0432, 0x5a);
+        cpu.register_x = 0x01;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x5a);
+    }
+
+    #[test]
+    fn test_lda_indirect_x_pointer_wraps_in_zero_page() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa1, 0xfe, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0xff, 0x32);
+        cpu.mem_write(0x00, 0x04);
+        cpu.mem_write(0x0100, 0x05);
+        cpu.mem_write(0x0432, 0x5b);
+        cpu.register_x = 0x01;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x5b);
+    }
+
+    #[test]
+    fn test_lda_indirect_y_pointer_wraps_in_zero_page() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xb1, 0xff, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0xff, 0x31);
+        cpu.mem_write(0x00, 0x04);
+        cpu.mem_write(0x0100, 0x05);
+        cpu.mem_write(0x0432, 0x5c);
+        cpu.register_y = 0x01;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x5c);
+    }
+
+    #[test]
+    fn test_lda_indirect_y() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xb1, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write_u16(0x10, 0x0754);
+        cpu.mem_write(0x0764, 0x5b);
+        cpu.register_y = 0x10;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x5b);
+    }
+
+    /* NMI */
+    #[test]
+    fn test_nmi_ignores_interrupt_disable_flag() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa9, 0x01, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0780, 0xa9);
+        cpu.mem_write(0x0781, 0x42);
+        cpu.mem_write(0x0782, 0x00);
+        cpu.status = 0b00000100;
+        cpu.set_nmi_line(true);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x42);
+        assert_eq!(cpu.mem_read_u16(0x01fc), 0x0600);
+        assert_eq!(cpu.mem_read(0x01fb), 0b00100100);
+    }
+
+    #[test]
+    fn test_nmi_is_edge_triggered() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xea, 0xea, 0xea, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0780, 0xe8);
+        cpu.mem_write(0x0781, 0x40);
+        cpu.set_nmi_line(true);
+        cpu.run_with_callback(|cpu| cpu.set_nmi_line(true));
+        assert_eq!(cpu.register_x, 0x01);
+        assert_eq!(cpu.stack_pointer, STACK_RESET);
+    }
+
+    #[test]
+    fn test_nmi_takes_priority_over_irq() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0700, 0xa9);
+        cpu.mem_write(0x0701, 0x01);
+        cpu.mem_write(0x0702, 0x00);
+        cpu.mem_write(0x0780, 0xa9);
+        cpu.mem_write(0x0781, 0x02);
+        cpu.mem_write(0x0782, 0x00);
+        cpu.set_irq_line(true);
+        cpu.set_nmi_line(true);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x02);
+    }
+
+    /* ORA */
+    #[test]
+    fn test_ora() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x09, 0x0C, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x0A;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x0E);
+        assert_eq!(cpu.status, 0);
+    }
+
+    /* Stack */
+    #[test]
+    fn test_stack_push_is_visible_through_ram_mirrors() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x48, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x42;
+        cpu.run();
+        assert_eq!(cpu.stack_pointer, STACK_RESET - 1);
+        assert_eq!(cpu.mem_read(0x01fd), 0x42);
+        assert_eq!(cpu.mem_read(0x09fd), 0x42);
+        assert_eq!(cpu.mem_read(0x11fd), 0x42);
+        assert_eq!(cpu.mem_read(0x19fd), 0x42);
+    }
+
+    #[test]
+    fn test_stack_pop_reads_values_written_through_mirror() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x68, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x19fe, 0x37);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x37);
+        assert_eq!(cpu.stack_pointer, STACK_RESET + 1);
+    }
+
+    #[test]
+    fn test_rts_returns_to_address_written_through_mirror() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x60, 0x00, 0x00, 0x00, 0xa9, 0x05, 0x00]);
+        cpu.reset();
+        cpu.stack_pointer = 0xfb;
+        cpu.mem_write_u16(0x09fc, 0x0603);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x05);
+    }
+
+    /* STA */
+    #[test]
+    fn test_sta() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x85, 0x10, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x5c;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x5c);
+    }
+
+    /* TAX */
+    #[test]
+    fn test_tax() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xaa, 0x00]);
+        cpu.reset();
+        cpu.register_a = 10;
+        cpu.run();
+        assert_eq!(cpu.register_x, 10);
+    }
+
+    /* Unofficial opcodes */
+    #[test]
+    fn test_ahx_masks_with_high_byte_plus_one() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x9f, 0x00, 0x02, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x0f;
+        cpu.register_x = 0xff;
+        cpu.register_y = 0x10;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x0210), 0x03);
+    }
+
+    #[test]
+    fn test_alr() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x4b, 0x03, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0xff;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x01);
+        assert_eq!(cpu.status, 0b00000001);
+    }
+
+    #[test]
+    fn test_anc() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x0b, 0x80, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0xff;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x80);
+        assert_eq!(cpu.status, 0b10000001);
+    }
+
+    #[test]
+    fn test_arr() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x38, 0x6b, 0xff, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0xc0;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0xe0);
+        assert_eq!(cpu.status, 0b10000001);
+    }
+
+    #[test]
+    fn test_axs() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xcb, 0x02, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x0f;
+        cpu.register_x = 0x06;
+        cpu.run();
+        assert_eq!(cpu.register_x, 0x04);
+        assert_eq!(cpu.register_a, 0x0f);
+        assert_eq!(cpu.status, 0b00000001);
+    }
+
+    #[test]
+    fn test_dcp() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xc7, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x06);
+        cpu.register_a = 0x05;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x05);
+        assert_eq!(cpu.status, 0b00000011);
+    }
+
+    #[test]
+    fn test_isb() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x38, 0xe7, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x01);
+        cpu.register_a = 0x05;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x02);
+        assert_eq!(cpu.register_a, 0x03);
+        assert_eq!(cpu.status, 0b00000001);
+    }
+
+    #[test]
+    fn test_isb_wrapping_to_zero_keeps_carry() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x38, 0xe7, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0xff);
+        cpu.register_a = 0x05;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x00);
+        assert_eq!(cpu.register_a, 0x05);
+        assert_eq!(cpu.status, 0b00000001);
+    }
+
+    #[test]
+    fn test_jam_stops_execution() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xa9, 0x01, 0x02, 0xa9, 0x02, 0x00]);
+        assert_eq!(cpu.register_a, 0x01);
+    }
+
+    #[test]
+    fn test_las() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xbb, 0x00, 0x02, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0210, 0xf0);
+        cpu.register_y = 0x10;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0xf0);
+        assert_eq!(cpu.register_x, 0xf0);
+        assert_eq!(cpu.stack_pointer, 0xf0);
+        assert_eq!(cpu.status, 0b10000000);
+    }
+
+    #[test]
+    fn test_lax() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa7, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x80);
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x80);
+        assert_eq!(cpu.register_x, 0x80);
+        assert_eq!(cpu.status, 0b10000000);
+    }
+
+    #[test]
+    fn test_lxa() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xab, 0x5a, 0x00]);
+        assert_eq!(cpu.register_a, 0x5a);
+        assert_eq!(cpu.register_x, 0x5a);
+        assert_eq!(cpu.status, 0);
+    }
+
+    #[test]
+    fn test_multi_byte_nops() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![
+            0x0c, 0x34, 0x12, 0x80, 0x55, 0x1c, 0xff, 0x02, 0xa9, 0x01, 0x00,
+        ]);
+        cpu.reset();
+        cpu.register_x = 0x01;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x01);
+        assert_eq!(cpu.cycles, 7 + 4 + 2 + 5 + 2);
+    }
+
+    #[test]
+    fn test_rla() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x38, 0x27, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x80);
+        cpu.register_a = 0xff;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x01);
+        assert_eq!(cpu.register_a, 0x01);
+        assert_eq!(cpu.status, 0b00000001);
+    }
+
+    #[test]
+    fn test_rra() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x67, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x03);
+        cpu.register_a = 0x10;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x01);
+        assert_eq!(cpu.register_a, 0x12);
+        assert_eq!(cpu.status, 0);
+    }
+
+    #[test]
+    fn test_sax() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x87, 0x10, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x0f;
+        cpu.register_x = 0x3c;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x0c);
+        assert_eq!(cpu.status, 0);
+    }
+
+    #[test]
+    fn test_shx_masks_with_high_byte_plus_one() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x9e, 0x00, 0x03, 0x00]);
+        cpu.reset();
+        cpu.register_x = 0xff;
+        cpu.register_y = 0x01;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x0301), 0x04);
+    }
+
+    #[test]
+    fn test_shy_masks_with_high_byte_plus_one() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x9c, 0x00, 0x04, 0x00]);
+        cpu.reset();
+        cpu.register_x = 0x02;
+        cpu.register_y = 0xff;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x0402), 0x05);
+    }
+
+    #[test]
+    fn test_unofficial_sbc() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x38, 0xeb, 0x02, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0x05;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x03);
+        assert_eq!(cpu.status, 0b00000001);
+    }
+
+    #[test]
+    fn test_slo() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x07, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x41);
+        cpu.register_a = 0x02;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x82);
+        assert_eq!(cpu.register_a, 0x82);
+        assert_eq!(cpu.status, 0b10000000);
+    }
+
+    #[test]
+    fn test_sre() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x47, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x10, 0x03);
+        cpu.register_a = 0xff;
+        cpu.run();
+        assert_eq!(cpu.mem_read(0x10), 0x01);
+        assert_eq!(cpu.register_a, 0xfe);
+        assert_eq!(cpu.status, 0b10000001);
+    }
+
+    #[test]
+    fn test_tas() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x9b, 0x00, 0x02, 0x00]);
+        cpu.reset();
+        cpu.register_a = 0xff;
+        cpu.register_x = 0xf7;
+        cpu.register_y = 0x01;
+        cpu.run();
+        assert_eq!(cpu.stack_pointer, 0xf7);
+        assert_eq!(cpu.mem_read(0x0201), 0x03);
+    }
+
+    #[test]
+    fn test_xaa() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x8b, 0x0f, 0x00]);
+        cpu.reset();
+        cpu.register_x = 0x35;
+        cpu.run();
+        assert_eq!(cpu.register_a, 0x05);
+        assert_eq!(cpu.register_x, 0x35);
+    }
+
+    /* Cycles */
+    #[test]
+    fn test_cycles_after_reset() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0x00]);
+        assert_eq!(cpu.cycles, 7);
+    }
+
+    #[test]
+    fn test_cycles_accumulate_base_counts() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xa9, 0x01, 0x85, 0x10, 0xee, 0x10, 0x00, 0x48, 0x00]);
+        cpu.reset();
+        cpu.run();
+        assert_eq!(cpu.cycles, 7 + 2 + 3 + 6 + 3);
+    }
+
+    #[test]
+    fn test_page_cross_cycle_on_indexed_read() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xbd, 0xff, 0x02, 0x00]);
+        cpu.reset();
+        cpu.register_x = 0x01;
+        cpu.run();
+        assert_eq!(cpu.cycles, 7 + 4 + 1);
+    }
+
+    #[test]
+    fn test_no_page_cross_cycle_without_crossing() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xb1, 0x10, 0x00]);
+        cpu.reset();
+        cpu.mem_write_u16(0x10, 0x0280);
+        cpu.register_y = 0x7f;
+        cpu.run();
+        assert_eq!(cpu.cycles, 7 + 5);
+    }
+
+    #[test]
+    fn test_no_page_cross_cycle_on_indexed_store() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x9d, 0xff, 0x02, 0x00]);
+        cpu.reset();
+        cpu.register_x = 0x01;
+        cpu.run();
+        assert_eq!(cpu.cycles, 7 + 5);
+    }
+
+    #[test]
+    fn test_branch_cycles() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xf0, 0x01, 0x00]);
+        assert_eq!(cpu.cycles, 7 + 2);
+
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xd0, 0x01, 0x00, 0x00]);
+        assert_eq!(cpu.cycles, 7 + 2 + 1);
+
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xd0, 0xf0, 0x00]);
+        assert_eq!(cpu.program_counter, 0x05f2);
+        assert_eq!(cpu.cycles, 7 + 2 + 2);
+    }
+
+    /* Other */
+    #[test]
+    fn test_5_ops_working_together() {
+        let mut cpu = test_cpu();
+        cpu.load_and_run(vec![0xa9, 0xc0, 0xaa, 0xe8, 0x00]);
+        assert_eq!(cpu.register_x, 0xc1);
+    }
+
+    #[test]
+    fn test_run_for_cycles_stops_at_instruction_boundary() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xe8, 0xe8, 0xe8, 0xe8, 0x00]);
+        cpu.reset();
+
+        assert_eq!(cpu.run_for_cycles(5), 6);
+        assert_eq!(cpu.register_x, 3);
+        assert_eq!(cpu.program_counter, 0x0603);
+    }
+
+    #[test]
+    fn test_run_for_cycles_stops_on_halt() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xe8, 0x00]);
+        cpu.reset();
+
+        assert_eq!(cpu.run_for_cycles(100), 2);
+        assert_eq!(cpu.register_x, 1);
+    }
+
+    #[test]
+    fn test_run_until_pc() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xe8, 0xe8, 0xe8, 0xe8, 0x00]);
+        cpu.reset();
+
+        assert!(cpu.run_until_pc(0x0602));
+        assert_eq!(cpu.program_counter, 0x0602);
+        assert_eq!(cpu.register_x, 2);
+        assert!(cpu.run_until_pc(0x0602));
+        assert_eq!(cpu.register_x, 2);
+    }
+
+    #[test]
+    fn test_run_until_pc_stops_on_halt() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xe8, 0x00, 0xe8]);
+        cpu.reset();
+
+        assert!(!cpu.run_until_pc(0x0602));
+        assert!(cpu.is_halted());
+        assert_eq!(cpu.program_counter, 0x0601);
+        assert_eq!(cpu.register_x, 1);
+    }
+
+    #[test]
+    fn test_run_until_pc_stops_at_interrupt_handler() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xea, 0xea, 0xea, 0xea, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0780, 0xe8);
+        cpu.mem_write(0x0781, 0x40);
+        cpu.step();
+        cpu.set_nmi_line(true);
+
+        assert!(cpu.run_until_pc(0x0780));
+        assert_eq!(cpu.register_x, 0);
+        assert_eq!(cpu.cycles, 7 + 2 + 7);
+    }
+
+    #[test]
+    fn test_step_returns_instruction_info() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xad, 0x10, 0x02, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0210, 0x55);
+
+        let result = StepResult {
+            opcode: 0xad,
+            mnemonic: "LDA",
+            len: 3,
+            operand_bytes: [0x10, 0x02],
+            mode: AddressingMode::Absolute,
+            cycles: 4,
+            program_counter: 0x0603,
+        };
+        assert_eq!(cpu.step(), Step::Instruction(result));
+        assert_eq!(result.operands(), &[0x10, 0x02]);
+        assert_eq!(cpu.register_a, 0x55);
+    }
+
+    #[test]
+    fn test_step_reports_taken_branch_cycles() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xd0, 0x02, 0x00, 0x00, 0x00]);
+        cpu.reset();
+
+        let Step::Instruction(result) = cpu.step() else {
+            panic!("expected an instruction");
+        };
+        assert_eq!(result.mnemonic, "BNE");
+        assert_eq!(result.mode, AddressingMode::Relative);
+        assert_eq!(result.operands(), &[0x02]);
+        assert_eq!(result.cycles, 3);
+        assert_eq!(result.program_counter, 0x0604);
+    }
+
+    #[test]
+    fn test_step_does_nothing_while_halted() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0x00, 0xa9, 0x42, 0x00]);
+        cpu.reset();
+
+        assert_eq!(cpu.step(), Step::Halted);
+        assert_eq!(cpu.step(), Step::Halted);
+        assert_eq!(cpu.register_a, 0x00);
+        assert_eq!(cpu.program_counter, 0x0600);
+        assert_eq!(cpu.cycles, 7);
+    }
+
+    #[test]
+    fn test_step_reports_interrupt_entry_separately() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xea, 0x00]);
+        cpu.reset();
+        cpu.mem_write(0x0780, 0xe8);
+        cpu.set_nmi_line(true);
+
+        assert_eq!(cpu.step(), Step::Interrupt(Interrupt::Nmi));
+        assert_eq!(cpu.program_counter, 0x0780);
+        assert_eq!(cpu.cycles, 7 + 7);
+
+        let Step::Instruction(result) = cpu.step() else {
+            panic!("expected an instruction");
+        };
+        assert_eq!(result.mnemonic, "INX");
+        assert_eq!(result.cycles, 2);
+        assert_eq!(cpu.register_x, 1);
+    }
+
+    #[test]
+    fn test_step_records_each_operand_read_once() {
+        let mut cpu = test_cpu();
+        cpu.load(vec![0xbd, 0x10, 0x02, 0x00]);
+        cpu.reset();
+        cpu.register_x = 0x01;
+        cpu.bus.start_heatmap(1);
+        cpu.step();
+
+        let heatmap = cpu.bus.stop_heatmap().unwrap();
+        assert_eq!(&heatmap.reads()[0x0600..0x0603], &[1, 1, 1]);
+        assert_eq!(heatmap.reads()[0x0211], 1);
+        assert_eq!(heatmap.reads().iter().sum::<u32>(), 4);
+    }
+
+    #[test]
+    fn test_step_records_each_rmw_read_once() {
+        for code in [0xdf, 0xff, 0x1f, 0x3f, 0x5f, 0x7f] {
+            let mut cpu = test_cpu();
+            cpu.load(vec![code, 0x10, 0x02, 0x00]);
+            cpu.reset();
+            cpu.register_x = 0x01;
+            cpu.bus.start_heatmap(1);
+            cpu.step();
+
+            let heatmap = cpu.bus.stop_heatmap().unwrap();
+            assert_eq!(
+                &heatmap.reads()[0x0600..0x0603],
+                &[1, 1, 1],
+                "{:#04x}",
+                code
+            );
+            assert_eq!(heatmap.reads()[0x0211], 1, "{:#04x}", code);
+            assert_eq!(heatmap.reads().iter().sum::<u32>(), 4, "{:#04x}", code);
+            assert_eq!(heatmap.writes()[0x0211], 1, "{:#04x}", code);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+pub struct Heatmap {
+    granularity: usize,
+    reads: Vec<u32>,
+    writes: Vec<u32>,
+    executes: Vec<u32>,
+}
+
+impl Heatmap {
+    pub fn new(granularity: usize) -> Self {
+        if granularity == 0 {
+            panic!("Heatmap granularity must be at least 1 byte");
+        }
+
+        let size = 0x10000usize.div_ceil(granularity);
+        Heatmap {
+            granularity,
+            reads: vec![0; size],
+            writes: vec![0; size],
+            executes: vec![0; size],
+        }
+    }
+
+    pub fn record(&mut self, access: Access, addr: u16) {
+        let counts = match access {
+            Access::Read => &mut self.reads,
+            Access::Write => &mut self.writes,
+            Access::Execute => &mut self.executes,
+        };
+        let idx = addr as usize / self.granularity;
+        counts[idx] = counts[idx].saturating_add(1);
+    }
+
+    pub fn granularity(&self) -> usize {
+        self.granularity
+    }
+
+    pub fn reads(&self) -> &[u32] {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &[u32] {
+        &self.writes
+    }
+
+    pub fn executes(&self) -> &[u32] {
+        &self.executes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bucket_size_follows_granularity() {
+        assert_eq!(Heatmap::new(1).reads().len(), 0x10000);
+        assert_eq!(Heatmap::new(0x100).writes().len(), 0x100);
+        assert_eq!(Heatmap::new(0x3000).executes().len(), 6);
+    }
+
+    #[test]
+    fn test_record_buckets_addresses() {
+        let mut heatmap = Heatmap::new(0x10);
+        heatmap.record(Access::Read, 0x0000);
+        heatmap.record(Access::Read, 0x000f);
+        heatmap.record(Access::Read, 0x0010);
+        heatmap.record(Access::Write, 0xffff);
+        heatmap.record(Access::Execute, 0x8001);
+
+        assert_eq!(heatmap.reads()[0], 2);
+        assert_eq!(heatmap.reads()[1], 1);
+        assert_eq!(heatmap.writes()[0xfff], 1);
+        assert_eq!(heatmap.executes()[0x800], 1);
+        assert_eq!(heatmap.reads().iter().sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn test_counts_saturate() {
+        let mut heatmap = Heatmap::new(1);
+        heatmap.reads[0] = u32::MAX;
+        heatmap.record(Access::Read, 0x0000);
+        assert_eq!(heatmap.reads()[0], u32::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_granularity_panics() {
+        Heatmap::new(0);
+    }
+}
@@ -0,0 +1,125 @@
+const PATCH_TAG: &[u8] = b"PATCH";
+const EOF_TAG: &[u8] = b"EOF";
+const EOF_OFFSET: usize = 0x454f46;
+const MAX_OFFSET: usize = 0xffffff;
+const MAX_RECORD_SIZE: usize = 0xffff;
+
+pub fn create_patch(original: &[u8], modified: &[u8]) -> Result<Vec<u8>, String> {
+    let mut patch = PATCH_TAG.to_vec();
+    let differs = |i: usize| original.get(i) != Some(&modified[i]);
+
+    let mut i = 0;
+    while i < modified.len() {
+        if !differs(i) {
+            i += 1;
+            continue;
+        }
+
+        // A record starting at 0x454f46 would be read back as the EOF marker
+        let mut start = i;
+        if start == EOF_OFFSET {
+            start -= 1;
+        }
+        if start > MAX_OFFSET {
+            return Err("IPS patches cannot address offsets above 16MB".to_string());
+        }
+
+        let mut end = i;
+        while end < modified.len() && differs(end) && end - start < MAX_RECORD_SIZE {
+            end += 1;
+        }
+
+        patch.extend_from_slice(&(start as u32).to_be_bytes()[1..]);
+        patch.extend_from_slice(&((end - start) as u16).to_be_bytes());
+        patch.extend_from_slice(&modified[start..end]);
+        i = end;
+    }
+
+    patch.extend_from_slice(EOF_TAG);
+    Ok(patch)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    pub fn apply_patch(original: &[u8], patch: &[u8]) -> Vec<u8> {
+        assert_eq!(&patch[..PATCH_TAG.len()], PATCH_TAG);
+        let mut output = original.to_vec();
+        let mut i = PATCH_TAG.len();
+        while &patch[i..i + EOF_TAG.len()] != EOF_TAG {
+            let offset = u32::from_be_bytes([0, patch[i], patch[i + 1], patch[i + 2]]) as usize;
+            let size = u16::from_be_bytes([patch[i + 3], patch[i + 4]]) as usize;
+            let data = &patch[i + 5..i + 5 + size];
+            if output.len() < offset + size {
+                output.resize(offset + size, 0);
+            }
+            output[offset..offset + size].copy_from_slice(data);
+            i += 5 + size;
+        }
+        output
+    }
+
+    #[test]
+    fn test_identical_input_has_no_records() {
+        assert_eq!(
+            create_patch(&[1, 2, 3], &[1, 2, 3]),
+            Ok(b"PATCHEOF".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_one_record_per_changed_run() {
+        let patch = create_patch(&[0; 8], &[0, 1, 2, 0, 0, 3, 0, 0]).unwrap();
+        assert_eq!(
+            patch,
+            [
+                b"PATCH".as_slice(),
+                &[0x00, 0x00, 0x01, 0x00, 0x02, 1, 2],
+                &[0x00, 0x00, 0x05, 0x00, 0x01, 3],
+                b"EOF",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_growing_file_is_recorded() {
+        let patch = create_patch(&[0; 2], &[0, 0, 7]).unwrap();
+        assert_eq!(patch, b"PATCH\x00\x00\x02\x00\x01\x07EOF".to_vec());
+    }
+
+    #[test]
+    fn test_record_at_eof_offset_is_shifted_back() {
+        let original = vec![0; EOF_OFFSET + 2];
+        let mut modified = original.clone();
+        modified[EOF_OFFSET] = 0xaa;
+
+        let patch = create_patch(&original, &modified).unwrap();
+        assert_eq!(patch, b"PATCH\x45\x4f\x45\x00\x02\x00\xaaEOF".to_vec());
+    }
+
+    #[test]
+    fn test_long_runs_are_split() {
+        let original = vec![0; MAX_RECORD_SIZE + 2];
+        let modified = vec![1; MAX_RECORD_SIZE + 2];
+
+        let patch = create_patch(&original, &modified).unwrap();
+        assert_eq!(patch.len(), 5 + (5 + MAX_RECORD_SIZE) + (5 + 2) + 3);
+        assert_eq!(&patch[5..10], &[0x00, 0x00, 0x00, 0xff, 0xff]);
+        let second = 10 + MAX_RECORD_SIZE;
+        assert_eq!(&patch[second..second + 5], &[0x00, 0xff, 0xff, 0x00, 0x02]);
+    }
+
+    #[test]
+    fn test_offsets_above_16mb_are_rejected() {
+        let original = vec![0; MAX_OFFSET + 2];
+        let mut modified = original.clone();
+        modified[MAX_OFFSET + 1] = 1;
+        assert!(create_patch(&original, &modified).is_err());
+
+        modified[MAX_OFFSET + 1] = 0;
+        modified[MAX_OFFSET] = 1;
+        assert!(create_patch(&original, &modified).is_ok());
+    }
+}
@@ -1,7 +1,11 @@
 pub mod bus;
 pub mod cartridge;
+pub mod coverage;
 pub mod cpu;
+pub mod heatmap;
+pub mod ips;
 pub mod opcodes;
+pub mod ram_map;
 
 use bus::Bus;
 use cartridge::Rom;
@@ -101,9 +105,10 @@ fn main() {
 
     let bus = Bus::new(rom);
     let mut cpu = CPU::new(bus);
+    cpu.halt_on_brk = true;
     cpu.reset();
 
-    let mut screen_state = [0 as u8; 32 * 3 * 32];
+    let mut screen_state = [0u8; 32 * 3 * 32];
     let mut rng = rand::thread_rng();
 
     cpu.run_with_callback(move |cpu| {
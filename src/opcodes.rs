@@ -6,6 +6,7 @@ pub struct OpCode {
     pub mnemonic: &'static str,
     pub len: u8,
     pub cycles: u8,
+    pub page_cross_cycle: bool,
     pub mode: AddressingMode,
 }
 
@@ -16,9 +17,15 @@ impl OpCode {
             mnemonic,
             len,
             cycles,
+            page_cross_cycle: false,
             mode,
         }
     }
+
+    fn with_page_cross_cycle(mut self) -> Self {
+        self.page_cross_cycle = true;
+        self
+    }
 }
 
 lazy_static! {
@@ -28,20 +35,20 @@ lazy_static! {
         OpCode::new(0x65, "ADC", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0x75, "ADC", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0x6d, "ADC", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0x7d, "ADC", 3, 4, AddressingMode::Absolute_X),
-        OpCode::new(0x79, "ADC", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0x7d, "ADC", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0x79, "ADC", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
         OpCode::new(0x61, "ADC", 2, 6, AddressingMode::Indirect_X),
-        OpCode::new(0x71, "ADC", 2, 5, AddressingMode::Indirect_Y),
+        OpCode::new(0x71, "ADC", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
 
         /* AND */
         OpCode::new(0x29, "AND", 2, 2, AddressingMode::Immediate),
         OpCode::new(0x25, "AND", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0x35, "AND", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0x2d, "AND", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0x3d, "AND", 3, 4, AddressingMode::Absolute_X),
-        OpCode::new(0x39, "AND", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0x3d, "AND", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0x39, "AND", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
         OpCode::new(0x21, "AND", 2, 6, AddressingMode::Indirect_X),
-        OpCode::new(0x31, "AND", 2, 5, AddressingMode::Indirect_Y),
+        OpCode::new(0x31, "AND", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
 
         /* ASL */
         OpCode::new(0x0a, "ASL", 1, 2, AddressingMode::Accumulator),
@@ -98,10 +105,10 @@ lazy_static! {
         OpCode::new(0xc5, "CMP", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0xd5, "CMP", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0xcd, "CMP", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0xdd, "CMP", 3, 4, AddressingMode::Absolute_X),
-        OpCode::new(0xd9, "CMP", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0xdd, "CMP", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0xd9, "CMP", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
         OpCode::new(0xc1, "CMP", 2, 6, AddressingMode::Indirect_X),
-        OpCode::new(0xd1, "CMP", 2, 5, AddressingMode::Indirect_Y),
+        OpCode::new(0xd1, "CMP", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
 
         /* CPX */
         OpCode::new(0xe0, "CPX", 2, 2, AddressingMode::Immediate),
@@ -130,10 +137,10 @@ lazy_static! {
         OpCode::new(0x45, "EOR", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0x55, "EOR", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0x4d, "EOR", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0x5d, "EOR", 3, 4, AddressingMode::Absolute_X),
-        OpCode::new(0x59, "EOR", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0x5d, "EOR", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0x59, "EOR", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
         OpCode::new(0x41, "EOR", 2, 6, AddressingMode::Indirect_X),
-        OpCode::new(0x51, "EOR", 2, 5, AddressingMode::Indirect_Y),
+        OpCode::new(0x51, "EOR", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
 
         /* INC */
         OpCode::new(0xe6, "INC", 2, 5, AddressingMode::ZeroPage),
@@ -159,24 +166,24 @@ lazy_static! {
         OpCode::new(0xa5, "LDA", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0xb5, "LDA", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0xad, "LDA", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0xbd, "LDA", 3, 4, AddressingMode::Absolute_X),
-        OpCode::new(0xb9, "LDA", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0xbd, "LDA", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0xb9, "LDA", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
         OpCode::new(0xa1, "LDA", 2, 6, AddressingMode::Indirect_X),
-        OpCode::new(0xb1, "LDA", 2, 5, AddressingMode::Indirect_Y),
+        OpCode::new(0xb1, "LDA", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
 
         /* LDX */
         OpCode::new(0xa2, "LDX", 2, 2, AddressingMode::Immediate),
         OpCode::new(0xa6, "LDX", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0xb6, "LDX", 2, 4, AddressingMode::ZeroPage_Y),
         OpCode::new(0xae, "LDX", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0xbe, "LDX", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0xbe, "LDX", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
 
         /* LDY */
         OpCode::new(0xa0, "LDY", 2, 2, AddressingMode::Immediate),
         OpCode::new(0xa4, "LDY", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0xb4, "LDY", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0xac, "LDY", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0xbc, "LDY", 3, 4, AddressingMode::Absolute_X),
+        OpCode::new(0xbc, "LDY", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
 
         /* LSR */
         OpCode::new(0x4a, "LSR", 1, 2, AddressingMode::Accumulator),
@@ -193,10 +200,10 @@ lazy_static! {
         OpCode::new(0x05, "ORA", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0x15, "ORA", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0x0d, "ORA", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0x1d, "ORA", 3, 4, AddressingMode::Absolute_X),
-        OpCode::new(0x19, "ORA", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0x1d, "ORA", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0x19, "ORA", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
         OpCode::new(0x01, "ORA", 2, 6, AddressingMode::Indirect_X),
-        OpCode::new(0x11, "ORA", 2, 5, AddressingMode::Indirect_Y),
+        OpCode::new(0x11, "ORA", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
 
         /* PHA */
         OpCode::new(0x48, "PHA", 1, 3, AddressingMode::Implied),
@@ -235,10 +242,10 @@ lazy_static! {
         OpCode::new(0xe5, "SBC", 2, 3, AddressingMode::ZeroPage),
         OpCode::new(0xf5, "SBC", 2, 4, AddressingMode::ZeroPage_X),
         OpCode::new(0xed, "SBC", 3, 4, AddressingMode::Absolute),
-        OpCode::new(0xfd, "SBC", 3, 4, AddressingMode::Absolute_X),
-        OpCode::new(0xf9, "SBC", 3, 4, AddressingMode::Absolute_Y),
+        OpCode::new(0xfd, "SBC", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0xf9, "SBC", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
         OpCode::new(0xe1, "SBC", 2, 6, AddressingMode::Indirect_X),
-        OpCode::new(0xf1, "SBC", 2, 5, AddressingMode::Indirect_Y),
+        OpCode::new(0xf1, "SBC", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
 
         /* SEC */
         OpCode::new(0x38, "SEC", 1, 2, AddressingMode::Implied),
@@ -284,7 +291,158 @@ lazy_static! {
         OpCode::new(0x9a, "TXS", 1, 2, AddressingMode::NoneAddressing),
 
         /* TYA */
-        OpCode::new(0x98, "TYA", 1, 2, AddressingMode::NoneAddressing)
+        OpCode::new(0x98, "TYA", 1, 2, AddressingMode::NoneAddressing),
+
+        /* Unofficial opcodes */
+
+        /* *AHX */
+        OpCode::new(0x9f, "*AHX", 3, 5, AddressingMode::Absolute_Y),
+        OpCode::new(0x93, "*AHX", 2, 6, AddressingMode::Indirect_Y),
+
+        /* *ALR */
+        OpCode::new(0x4b, "*ALR", 2, 2, AddressingMode::Immediate),
+
+        /* *ANC */
+        OpCode::new(0x0b, "*ANC", 2, 2, AddressingMode::Immediate),
+        OpCode::new(0x2b, "*ANC", 2, 2, AddressingMode::Immediate),
+
+        /* *ARR */
+        OpCode::new(0x6b, "*ARR", 2, 2, AddressingMode::Immediate),
+
+        /* *AXS */
+        OpCode::new(0xcb, "*AXS", 2, 2, AddressingMode::Immediate),
+
+        /* *DCP */
+        OpCode::new(0xc7, "*DCP", 2, 5, AddressingMode::ZeroPage),
+        OpCode::new(0xd7, "*DCP", 2, 6, AddressingMode::ZeroPage_X),
+        OpCode::new(0xcf, "*DCP", 3, 6, AddressingMode::Absolute),
+        OpCode::new(0xdf, "*DCP", 3, 7, AddressingMode::Absolute_X),
+        OpCode::new(0xdb, "*DCP", 3, 7, AddressingMode::Absolute_Y),
+        OpCode::new(0xc3, "*DCP", 2, 8, AddressingMode::Indirect_X),
+        OpCode::new(0xd3, "*DCP", 2, 8, AddressingMode::Indirect_Y),
+
+        /* *ISB */
+        OpCode::new(0xe7, "*ISB", 2, 5, AddressingMode::ZeroPage),
+        OpCode::new(0xf7, "*ISB", 2, 6, AddressingMode::ZeroPage_X),
+        OpCode::new(0xef, "*ISB", 3, 6, AddressingMode::Absolute),
+        OpCode::new(0xff, "*ISB", 3, 7, AddressingMode::Absolute_X),
+        OpCode::new(0xfb, "*ISB", 3, 7, AddressingMode::Absolute_Y),
+        OpCode::new(0xe3, "*ISB", 2, 8, AddressingMode::Indirect_X),
+        OpCode::new(0xf3, "*ISB", 2, 8, AddressingMode::Indirect_Y),
+
+        /* *JAM */
+        OpCode::new(0x02, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x12, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x22, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x32, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x42, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x52, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x62, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x72, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x92, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0xb2, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0xd2, "*JAM", 1, 2, AddressingMode::Implied),
+        OpCode::new(0xf2, "*JAM", 1, 2, AddressingMode::Implied),
+
+        /* *LAS */
+        OpCode::new(0xbb, "*LAS", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
+
+        /* *LAX */
+        OpCode::new(0xa7, "*LAX", 2, 3, AddressingMode::ZeroPage),
+        OpCode::new(0xb7, "*LAX", 2, 4, AddressingMode::ZeroPage_Y),
+        OpCode::new(0xaf, "*LAX", 3, 4, AddressingMode::Absolute),
+        OpCode::new(0xbf, "*LAX", 3, 4, AddressingMode::Absolute_Y).with_page_cross_cycle(),
+        OpCode::new(0xa3, "*LAX", 2, 6, AddressingMode::Indirect_X),
+        OpCode::new(0xb3, "*LAX", 2, 5, AddressingMode::Indirect_Y).with_page_cross_cycle(),
+
+        /* *LXA */
+        OpCode::new(0xab, "*LXA", 2, 2, AddressingMode::Immediate),
+
+        /* *NOP */
+        OpCode::new(0x1a, "*NOP", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x3a, "*NOP", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x5a, "*NOP", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x7a, "*NOP", 1, 2, AddressingMode::Implied),
+        OpCode::new(0xda, "*NOP", 1, 2, AddressingMode::Implied),
+        OpCode::new(0xfa, "*NOP", 1, 2, AddressingMode::Implied),
+        OpCode::new(0x80, "*NOP", 2, 2, AddressingMode::Immediate),
+        OpCode::new(0x82, "*NOP", 2, 2, AddressingMode::Immediate),
+        OpCode::new(0x89, "*NOP", 2, 2, AddressingMode::Immediate),
+        OpCode::new(0xc2, "*NOP", 2, 2, AddressingMode::Immediate),
+        OpCode::new(0xe2, "*NOP", 2, 2, AddressingMode::Immediate),
+        OpCode::new(0x04, "*NOP", 2, 3, AddressingMode::ZeroPage),
+        OpCode::new(0x44, "*NOP", 2, 3, AddressingMode::ZeroPage),
+        OpCode::new(0x64, "*NOP", 2, 3, AddressingMode::ZeroPage),
+        OpCode::new(0x14, "*NOP", 2, 4, AddressingMode::ZeroPage_X),
+        OpCode::new(0x34, "*NOP", 2, 4, AddressingMode::ZeroPage_X),
+        OpCode::new(0x54, "*NOP", 2, 4, AddressingMode::ZeroPage_X),
+        OpCode::new(0x74, "*NOP", 2, 4, AddressingMode::ZeroPage_X),
+        OpCode::new(0xd4, "*NOP", 2, 4, AddressingMode::ZeroPage_X),
+        OpCode::new(0xf4, "*NOP", 2, 4, AddressingMode::ZeroPage_X),
+        OpCode::new(0x0c, "*NOP", 3, 4, AddressingMode::Absolute),
+        OpCode::new(0x1c, "*NOP", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0x3c, "*NOP", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0x5c, "*NOP", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0x7c, "*NOP", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0xdc, "*NOP", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+        OpCode::new(0xfc, "*NOP", 3, 4, AddressingMode::Absolute_X).with_page_cross_cycle(),
+
+        /* *RLA */
+        OpCode::new(0x27, "*RLA", 2, 5, AddressingMode::ZeroPage),
+        OpCode::new(0x37, "*RLA", 2, 6, AddressingMode::ZeroPage_X),
+        OpCode::new(0x2f, "*RLA", 3, 6, AddressingMode::Absolute),
+        OpCode::new(0x3f, "*RLA", 3, 7, AddressingMode::Absolute_X),
+        OpCode::new(0x3b, "*RLA", 3, 7, AddressingMode::Absolute_Y),
+        OpCode::new(0x23, "*RLA", 2, 8, AddressingMode::Indirect_X),
+        OpCode::new(0x33, "*RLA", 2, 8, AddressingMode::Indirect_Y),
+
+        /* *RRA */
+        OpCode::new(0x67, "*RRA", 2, 5, AddressingMode::ZeroPage),
+        OpCode::new(0x77, "*RRA", 2, 6, AddressingMode::ZeroPage_X),
+        OpCode::new(0x6f, "*RRA", 3, 6, AddressingMode::Absolute),
+        OpCode::new(0x7f, "*RRA", 3, 7, AddressingMode::Absolute_X),
+        OpCode::new(0x7b, "*RRA", 3, 7, AddressingMode::Absolute_Y),
+        OpCode::new(0x63, "*RRA", 2, 8, AddressingMode::Indirect_X),
+        OpCode::new(0x73, "*RRA", 2, 8, AddressingMode::Indirect_Y),
+
+        /* *SAX */
+        OpCode::new(0x87, "*SAX", 2, 3, AddressingMode::ZeroPage),
+        OpCode::new(0x97, "*SAX", 2, 4, AddressingMode::ZeroPage_Y),
+        OpCode::new(0x8f, "*SAX", 3, 4, AddressingMode::Absolute),
+        OpCode::new(0x83, "*SAX", 2, 6, AddressingMode::Indirect_X),
+
+        /* *SBC */
+        OpCode::new(0xeb, "*SBC", 2, 2, AddressingMode::Immediate),
+
+        /* *SHX */
+        OpCode::new(0x9e, "*SHX", 3, 5, AddressingMode::Absolute_Y),
+
+        /* *SHY */
+        OpCode::new(0x9c, "*SHY", 3, 5, AddressingMode::Absolute_X),
+
+        /* *SLO */
+        OpCode::new(0x07, "*SLO", 2, 5, AddressingMode::ZeroPage),
+        OpCode::new(0x17, "*SLO", 2, 6, AddressingMode::ZeroPage_X),
+        OpCode::new(0x0f, "*SLO", 3, 6, AddressingMode::Absolute),
+        OpCode::new(0x1f, "*SLO", 3, 7, AddressingMode::Absolute_X),
+        OpCode::new(0x1b, "*SLO", 3, 7, AddressingMode::Absolute_Y),
+        OpCode::new(0x03, "*SLO", 2, 8, AddressingMode::Indirect_X),
+        OpCode::new(0x13, "*SLO", 2, 8, AddressingMode::Indirect_Y),
+
+        /* *SRE */
+        OpCode::new(0x47, "*SRE", 2, 5, AddressingMode::ZeroPage),
+        OpCode::new(0x57, "*SRE", 2, 6, AddressingMode::ZeroPage_X),
+        OpCode::new(0x4f, "*SRE", 3, 6, AddressingMode::Absolute),
+        OpCode::new(0x5f, "*SRE", 3, 7, AddressingMode::Absolute_X),
+        OpCode::new(0x5b, "*SRE", 3, 7, AddressingMode::Absolute_Y),
+        OpCode::new(0x43, "*SRE", 2, 8, AddressingMode::Indirect_X),
+        OpCode::new(0x53, "*SRE", 2, 8, AddressingMode::Indirect_Y),
+
+        /* *TAS */
+        OpCode::new(0x9b, "*TAS", 3, 5, AddressingMode::Absolute_Y),
+
+        /* *XAA */
+        OpCode::new(0x8b, "*XAA", 2, 2, AddressingMode::Immediate)
     ];
 
 
@@ -0,0 +1,211 @@
+use crate::bus::{PPU_REGISTERS, PPU_REGISTERS_MIRRORS_END, PRG_ROM};
+use crate::cpu::Mem;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq)]
+pub enum VarType {
+    U8,
+    I8,
+    U16,
+    Bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Var {
+    pub addr: u16,
+    pub var_type: VarType,
+}
+
+pub struct RamMap {
+    vars: HashMap<String, Var>,
+}
+
+impl RamMap {
+    pub fn new(text: &str) -> Result<RamMap, String> {
+        let mut vars = HashMap::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(pos) => &line[..pos],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (addr, rest) = line
+                .split_once(':')
+                .ok_or(format!("Line {}: expected `<addr>: <name>, <type>`", i + 1))?;
+            let (name, var_type) = rest
+                .split_once(',')
+                .ok_or(format!("Line {}: expected `<addr>: <name>, <type>`", i + 1))?;
+
+            let addr = addr.trim();
+            let addr = addr
+                .strip_prefix("0x")
+                .or_else(|| addr.strip_prefix('$'))
+                .ok_or(format!("Line {}: address must start with 0x or $", i + 1))?;
+            let addr = u16::from_str_radix(addr, 16)
+                .map_err(|_| format!("Line {}: invalid address", i + 1))?;
+
+            let var_type = match var_type.trim() {
+                "u8" => VarType::U8,
+                "i8" => VarType::I8,
+                "u16" => VarType::U16,
+                "bool" => VarType::Bool,
+                other => return Err(format!("Line {}: unknown type {}", i + 1, other)),
+            };
+
+            let size = if var_type == VarType::U16 { 2 } else { 1 };
+            let last = addr
+                .checked_add(size - 1)
+                .ok_or(format!("Line {}: variable runs past $FFFF", i + 1))?;
+            if addr <= PPU_REGISTERS_MIRRORS_END && last >= PPU_REGISTERS {
+                return Err(format!("Line {}: PPU registers cannot be mapped", i + 1));
+            }
+
+            let name = name.trim();
+            if vars.contains_key(name) {
+                return Err(format!("Line {}: duplicate variable {}", i + 1, name));
+            }
+            vars.insert(name.to_string(), Var { addr, var_type });
+        }
+
+        Ok(RamMap { vars })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Var> {
+        self.vars.get(name)
+    }
+
+    pub fn read<M: Mem>(&self, mem: &M, name: &str) -> Result<i32, String> {
+        let var = self.get(name).ok_or(format!("Unknown variable {}", name))?;
+        let value = match var.var_type {
+            VarType::U8 => mem.mem_read(var.addr) as i32,
+            VarType::I8 => mem.mem_read(var.addr) as i8 as i32,
+            VarType::U16 => mem.mem_read_u16(var.addr) as i32,
+            VarType::Bool => (mem.mem_read(var.addr) != 0) as i32,
+        };
+        Ok(value)
+    }
+
+    pub fn write<M: Mem>(&self, mem: &mut M, name: &str, value: i32) -> Result<(), String> {
+        let var = self.get(name).ok_or(format!("Unknown variable {}", name))?;
+        let size = if var.var_type == VarType::U16 { 2 } else { 1 };
+        if var.addr + (size - 1) >= PRG_ROM {
+            return Err(format!("Variable {} is in read-only PRG ROM", name));
+        }
+        let out_of_range =
+            |_| format!("Value {} does not fit {} ({:?})", value, name, var.var_type);
+        match var.var_type {
+            VarType::U8 => mem.mem_write(var.addr, u8::try_from(value).map_err(out_of_range)?),
+            VarType::I8 => {
+                mem.mem_write(var.addr, i8::try_from(value).map_err(out_of_range)? as u8)
+            }
+            VarType::U16 => {
+                mem.mem_write_u16(var.addr, u16::try_from(value).map_err(out_of_range)?)
+            }
+            VarType::Bool => mem.mem_write(var.addr, (value != 0) as u8),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::test_rom;
+
+    #[test]
+    fn test_parse_entries() {
+        let map = RamMap::new("# SMB\n0x075A: lives, u8\n$0057: speed, i8 # signed\n\n").unwrap();
+        assert_eq!(
+            map.get("lives"),
+            Some(&Var {
+                addr: 0x075a,
+                var_type: VarType::U8
+            })
+        );
+        assert_eq!(
+            map.get("speed"),
+            Some(&Var {
+                addr: 0x0057,
+                var_type: VarType::I8
+            })
+        );
+        assert_eq!(map.get("coins"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(RamMap::new("075A: lives, u8").is_err());
+        assert!(RamMap::new("0x075A lives, u8").is_err());
+        assert!(RamMap::new("0x075A: lives").is_err());
+        assert!(RamMap::new("0x075A: lives, u32").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_name_is_rejected() {
+        let err = RamMap::new("0x0010: a, u8\n0x0011: a, bool").err().unwrap();
+        assert!(err.starts_with("Line 2:"));
+    }
+
+    #[test]
+    fn test_unreadable_addresses_are_rejected() {
+        assert!(RamMap::new("0x2002: status, u8").is_err());
+        assert!(RamMap::new("0x1fff: ptr, u16").is_err());
+        assert!(RamMap::new("0xffff: vector, u16").is_err());
+        assert!(RamMap::new("0xfffe: vector, u16").is_ok());
+    }
+
+    #[test]
+    fn test_typed_read_write() {
+        let map =
+            RamMap::new("0x10: hp, u8\n0x11: dx, i8\n0x12: score, u16\n0x14: dead, bool").unwrap();
+        let mut bus = Bus::new(test_rom());
+
+        map.write(&mut bus, "dx", -2).unwrap();
+        map.write(&mut bus, "score", 0x1234).unwrap();
+        map.write(&mut bus, "dead", 5).unwrap();
+        assert_eq!(map.read(&bus, "dx"), Ok(-2));
+        assert_eq!(bus.mem_read(0x11), 0xfe);
+        assert_eq!(map.read(&bus, "score"), Ok(0x1234));
+        assert_eq!(bus.mem_read(0x14), 1);
+        assert_eq!(map.read(&bus, "hp"), Ok(0));
+        assert!(map.read(&bus, "lives").is_err());
+    }
+
+    #[test]
+    fn test_write_out_of_range_is_rejected() {
+        let map = RamMap::new("0x10: hp, u8\n0x11: dx, i8\n0x12: score, u16").unwrap();
+        let mut bus = Bus::new(test_rom());
+
+        assert!(map.write(&mut bus, "hp", 300).is_err());
+        assert!(map.write(&mut bus, "hp", -1).is_err());
+        assert!(map.write(&mut bus, "dx", 200).is_err());
+        assert!(map.write(&mut bus, "dx", -129).is_err());
+        assert!(map.write(&mut bus, "score", 0x10000).is_err());
+        assert_eq!(bus.mem_read(0x10), 0);
+        assert_eq!(bus.mem_read(0x11), 0);
+
+        map.write(&mut bus, "hp", 255).unwrap();
+        map.write(&mut bus, "dx", -128).unwrap();
+        map.write(&mut bus, "score", 0xffff).unwrap();
+        assert_eq!(map.read(&bus, "hp"), Ok(255));
+        assert_eq!(map.read(&bus, "dx"), Ok(-128));
+        assert_eq!(map.read(&bus, "score"), Ok(0xffff));
+    }
+
+    #[test]
+    fn test_write_to_prg_rom_is_rejected() {
+        let map = RamMap::new("0xfffc: reset, u16").unwrap();
+        let mut bus = Bus::new(test_rom());
+        assert_eq!(map.read(&bus, "reset"), Ok(0x0600));
+        assert!(map.write(&mut bus, "reset", 0).is_err());
+
+        let map = RamMap::new("0x7fff: straddle, u16").unwrap();
+        assert!(map.write(&mut bus, "straddle", 0).is_err());
+    }
+}